@@ -0,0 +1,300 @@
+//! Procedural macros for cetane. Use them through the `macros` feature of
+//! the `cetane` crate rather than depending on this crate directly.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use proc_macro::TokenStream;
+
+const BUILTIN_BACKENDS: [&str; 3] = ["mysql", "postgres", "sqlite"];
+const DEPENDS_ON_HEADER: &str = "depends_on:";
+
+/// Embed a directory of raw SQL migrations as `RunSql` migrations.
+///
+/// See `cetane::embed_sql_migrations!` for the directory layout.
+#[proc_macro]
+pub fn embed_sql_migrations(input: TokenStream) -> TokenStream {
+    let code = match expand(input) {
+        Ok(code) => code,
+        Err(message) => format!("compile_error!({:?})", message),
+    };
+    code.parse().expect("generated code is valid")
+}
+
+fn expand(input: TokenStream) -> Result<String, String> {
+    let relative = parse_path_literal(input)?;
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|_| "embed_sql_migrations!: CARGO_MANIFEST_DIR is not set".to_string())?;
+    let root = Path::new(&manifest_dir).join(relative);
+
+    let migrations = read_migrations(&root)?;
+
+    let mut code = String::from(
+        "{ let mut migrations = ::std::vec::Vec::<::cetane::migration::Migration>::new();",
+    );
+    for migration in &migrations {
+        code.push_str(&format!("migrations.push({});", migration.generate()?));
+    }
+    code.push_str("migrations }");
+    Ok(code)
+}
+
+fn parse_path_literal(input: TokenStream) -> Result<String, String> {
+    syn::parse::<syn::LitStr>(input)
+        .map(|literal| literal.value())
+        .map_err(|_| "embed_sql_migrations!: expected a single string literal".to_string())
+}
+
+#[derive(Default)]
+struct SqlFiles {
+    generic: Option<PathBuf>,
+    by_backend: BTreeMap<String, PathBuf>,
+}
+
+impl SqlFiles {
+    fn is_empty(&self) -> bool {
+        self.generic.is_none() && self.by_backend.is_empty()
+    }
+
+    fn for_backend(&self, backend: &str) -> Option<&PathBuf> {
+        self.by_backend.get(backend).or(self.generic.as_ref())
+    }
+}
+
+struct SqlMigration {
+    name: String,
+    up: SqlFiles,
+    down: SqlFiles,
+    dependencies: Vec<String>,
+}
+
+fn read_migrations(root: &Path) -> Result<Vec<SqlMigration>, String> {
+    let entries = fs::read_dir(root).map_err(|e| cannot_read(root, e))?;
+
+    let mut dirs = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|e| cannot_read(root, e))?.path();
+        if path.is_dir() {
+            dirs.push(path);
+        }
+    }
+    dirs.sort();
+
+    let mut migrations: Vec<SqlMigration> = Vec::new();
+    for dir in dirs {
+        let previous = migrations.last().map(|m| m.name.clone());
+        migrations.push(read_migration(&dir, previous)?);
+    }
+    Ok(migrations)
+}
+
+fn read_migration(dir: &Path, previous: Option<String>) -> Result<SqlMigration, String> {
+    let name = dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| format!("embed_sql_migrations!: invalid directory {}", dir.display()))?
+        .to_string();
+
+    let mut up = SqlFiles::default();
+    let mut down = SqlFiles::default();
+
+    let entries = fs::read_dir(dir).map_err(|e| cannot_read(dir, e))?;
+
+    for entry in entries {
+        let path = entry.map_err(|e| cannot_read(dir, e))?.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Some(stem) = file_name.strip_suffix(".sql") else {
+            continue;
+        };
+        let (direction, backend) = match stem.split_once('.') {
+            Some((direction, backend)) => (direction, Some(backend.to_string())),
+            None => (stem, None),
+        };
+        let files = match direction {
+            "up" => &mut up,
+            "down" => &mut down,
+            _ => continue,
+        };
+        match backend {
+            Some(backend) if !BUILTIN_BACKENDS.contains(&backend.as_str()) => {
+                return Err(format!(
+                    "embed_sql_migrations!: {} is for unknown backend {:?}; expected one of {}",
+                    path.display(),
+                    backend,
+                    BUILTIN_BACKENDS.join(", ")
+                ));
+            }
+            Some(backend) => {
+                files.by_backend.insert(backend, path.clone());
+            }
+            None => files.generic = Some(path.clone()),
+        }
+    }
+
+    if up.is_empty() {
+        return Err(format!(
+            "embed_sql_migrations!: {} has no up.sql",
+            dir.display()
+        ));
+    }
+
+    let header_file = up
+        .generic
+        .as_ref()
+        .or_else(|| up.by_backend.values().next())
+        .expect("up files are not empty");
+    let dependencies = match read_dependency_header(header_file)? {
+        Some(dependencies) => dependencies,
+        None => previous.into_iter().collect(),
+    };
+
+    Ok(SqlMigration {
+        name,
+        up,
+        down,
+        dependencies,
+    })
+}
+
+/// Read an explicit `-- depends_on: a, b` header from the leading comments
+/// of a SQL file. An empty list declares no dependencies.
+fn read_dependency_header(path: &Path) -> Result<Option<Vec<String>>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| cannot_read(path, e))?;
+
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() {
+            continue;
+        }
+        let Some(comment) = line.strip_prefix("--") else {
+            break;
+        };
+        if let Some(list) = comment.trim().strip_prefix(DEPENDS_ON_HEADER) {
+            return Ok(Some(
+                list.split(',')
+                    .map(str::trim)
+                    .filter(|dep| !dep.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            ));
+        }
+    }
+
+    Ok(None)
+}
+
+fn cannot_read(path: &Path, error: std::io::Error) -> String {
+    format!(
+        "embed_sql_migrations!: cannot read {}: {}",
+        path.display(),
+        error
+    )
+}
+
+fn include(path: &Path) -> Result<String, String> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| format!("embed_sql_migrations!: non-UTF-8 path {}", path.display()))?;
+    Ok(format!("include_str!({:?})", path))
+}
+
+impl SqlMigration {
+    fn generate(&self) -> Result<String, String> {
+        let dependencies: Vec<String> = self
+            .dependencies
+            .iter()
+            .map(|dep| format!("{:?}", dep))
+            .collect();
+
+        Ok(format!(
+            "::cetane::migration::Migration::new({:?}).depends_on(&[{}]).operation({})",
+            self.name,
+            dependencies.join(", "),
+            self.generate_operation()?
+        ))
+    }
+
+    fn generate_operation(&self) -> Result<String, String> {
+        let portable = !self.up.by_backend.is_empty() || !self.down.by_backend.is_empty();
+
+        if !portable {
+            let up = include(self.up.generic.as_ref().expect("up files are not empty"))?;
+            return Ok(match self.down.generic {
+                Some(ref down) => format!(
+                    "::cetane::operation::RunSql::reversible({}, {})",
+                    up,
+                    include(down)?
+                ),
+                None => format!("::cetane::operation::RunSql::new({})", up),
+            });
+        }
+
+        let mut backends: BTreeSet<&str> = self
+            .up
+            .by_backend
+            .keys()
+            .chain(self.down.by_backend.keys())
+            .map(String::as_str)
+            .collect();
+        if self.up.generic.is_some() {
+            backends.extend(BUILTIN_BACKENDS);
+        }
+
+        let reversible = !self.down.is_empty();
+        let mut code = String::from("::cetane::operation::RunSql::portable()");
+
+        for backend in backends {
+            let up = self.up.for_backend(backend).ok_or_else(|| {
+                format!(
+                    "embed_sql_migrations!: {} has down.{}.sql but no up SQL for {}",
+                    self.name, backend, backend
+                )
+            })?;
+
+            if reversible {
+                let down = self.down.for_backend(backend).ok_or_else(|| {
+                    format!(
+                        "embed_sql_migrations!: {} has no down SQL for {}",
+                        self.name, backend
+                    )
+                })?;
+                code.push_str(&format!(
+                    ".for_backend_reversible({:?}, {}, {})",
+                    backend,
+                    include(up)?,
+                    include(down)?
+                ));
+            } else {
+                code.push_str(&format!(".for_backend({:?}, {})", backend, include(up)?));
+            }
+        }
+
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_unknown_backend_suffixes() {
+        let dir =
+            std::env::temp_dir().join(format!("cetane_macros_{}/0001_initial", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("up.sql"), "SELECT 1;").unwrap();
+        fs::write(dir.join("up.postgresql.sql"), "SELECT 1;").unwrap();
+
+        let result = read_migration(&dir, None);
+        fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+
+        let error = result.err().expect("unknown backend is rejected");
+        assert!(
+            error.ends_with("up.postgresql.sql is for unknown backend \"postgresql\"; expected one of mysql, postgres, sqlite"),
+            "{}",
+            error
+        );
+    }
+}
@@ -7,13 +7,13 @@ pub use postgres::Postgres;
 pub use sqlite::Sqlite;
 
 use sea_query::{
-    Alias, ColumnDef, ForeignKey as SeaForeignKey, ForeignKeyAction, Index as SeaIndex,
-    IndexCreateStatement, IndexDropStatement, Table, TableAlterStatement, TableCreateStatement,
-    TableDropStatement, TableRenameStatement,
+    Alias, ColumnDef, DeleteStatement, Expr, ForeignKey as SeaForeignKey, ForeignKeyAction,
+    Index as SeaIndex, IndexCreateStatement, IndexDropStatement, InsertStatement, Table,
+    TableAlterStatement, TableCreateStatement, TableDropStatement, TableRenameStatement, Value,
 };
 
-use crate::field::{Field, FieldType, ReferentialAction};
-use crate::operation::{Constraint, Index, IndexOrder};
+use crate::field::{DefaultValue, Field, FieldType, ReferentialAction};
+use crate::operation::{Constraint, Index, IndexOrder, ScriptSyntax};
 
 pub trait Backend: Send + Sync {
     fn name(&self) -> &'static str;
@@ -22,19 +22,68 @@ pub trait Backend: Send + Sync {
     fn supports_drop_column(&self) -> bool;
     fn supports_transactional_ddl(&self) -> bool;
 
+    /// Whether enums are named types created with `CREATE TYPE`, rather than
+    /// spelled out in each column that uses them.
+    fn supports_enum_types(&self) -> bool {
+        false
+    }
+
+    /// Whether `field_type` has a native column type here. Fields whose type
+    /// doesn't are stored as its [`FieldType::fallback`].
+    fn supports_field_type(&self, _field_type: &FieldType) -> bool {
+        true
+    }
+
+    /// A column type to use for `field_type` instead of sea-query's, where
+    /// sea-query's rendering has the wrong type affinity or limits here.
+    fn column_type(&self, _field_type: &FieldType) -> Option<String> {
+        None
+    }
+
     fn build_table_create(&self, stmt: TableCreateStatement) -> String;
     fn build_table_drop(&self, stmt: TableDropStatement) -> String;
     fn build_table_rename(&self, stmt: TableRenameStatement) -> String;
     fn build_table_alter(&self, stmt: TableAlterStatement) -> String;
     fn build_index_create(&self, stmt: IndexCreateStatement) -> String;
     fn build_index_drop(&self, stmt: IndexDropStatement) -> String;
+    fn build_insert(&self, stmt: InsertStatement) -> String;
+    fn build_delete(&self, stmt: DeleteStatement) -> String;
+
+    /// Placeholder for the `n`th (1-based) bind value of a statement.
+    fn placeholder(&self, _n: usize) -> String {
+        "?".to_string()
+    }
+
+    /// Render a statement with its bind values inlined as literals, for SQL
+    /// previews and executors that don't take parameters.
+    fn inline_values(&self, sql: &str, values: &[Value]) -> String;
+
+    /// `value` as a quoted SQL string literal.
+    fn string_literal(&self, value: &str) -> String {
+        self.inline_values(&self.placeholder(1), &[Value::from(value)])
+    }
+
+    /// Make `col` hold one of `variants` of the enum `name`. The default
+    /// emulates the enum with a text column and a `CHECK` constraint.
+    fn enum_column(&self, col: &mut ColumnDef, column: &str, _name: &str, variants: &[String]) {
+        col.text().check(Expr::cust(format!(
+            "{} IN ({})",
+            self.quote_identifier(column),
+            string_list(self, variants)
+        )));
+    }
+
+    /// How raw SQL scripts are split into statements.
+    fn script_syntax(&self) -> ScriptSyntax {
+        ScriptSyntax::default()
+    }
 
     fn create_table_sql(&self, name: &str, fields: &[Field]) -> Vec<String> {
         let mut stmt = Table::create();
         stmt.table(Alias::new(name));
 
         for field in fields {
-            stmt.col(field_to_column_def(field));
+            stmt.col(field_to_column_def(self, field));
         }
 
         // Add foreign key constraints separately for fields that have them
@@ -69,7 +118,7 @@ pub trait Backend: Send + Sync {
     fn add_field_sql(&self, table: &str, field: &Field) -> Vec<String> {
         let stmt = Table::alter()
             .table(Alias::new(table))
-            .add_column(field_to_column_def(field))
+            .add_column(field_to_column_def(self, field))
             .to_owned();
         vec![self.build_table_alter(stmt)]
     }
@@ -99,7 +148,7 @@ pub trait Backend: Send + Sync {
         let mut col = ColumnDef::new(Alias::new(field_name));
 
         if let Some(ref field_type) = changes.field_type {
-            apply_column_type(&mut col, field_type);
+            apply_column_type(self, &mut col, field_name, field_type);
         }
 
         if let Some(nullable) = changes.nullable {
@@ -110,8 +159,8 @@ pub trait Backend: Send + Sync {
             }
         }
 
-        if let Some(Some(ref default_val)) = changes.default {
-            col.default(sea_query::Expr::cust(default_val));
+        if let Some(Some(ref default)) = changes.default {
+            col.default(sea_query::Expr::cust(default.to_sql(self)));
         }
 
         let stmt = Table::alter()
@@ -210,10 +259,39 @@ pub trait Backend: Send + Sync {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FieldChanges {
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "type", default, skip_serializing_if = "Option::is_none")
+    )]
     pub field_type: Option<FieldType>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub nullable: Option<bool>,
-    pub default: Option<Option<String>>,
+    /// `Some(None)` drops the default; serialized as an explicit `null`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "deserialize_default_change"
+        )
+    )]
+    pub default: Option<Option<DefaultValue>>,
+}
+
+/// Distinguishes an explicit `null` (drop the default) from a missing key.
+#[cfg(feature = "serde")]
+fn deserialize_default_change<'de, D>(
+    deserializer: D,
+) -> Result<Option<Option<DefaultValue>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    crate::field::deserialize_default(deserializer).map(Some)
 }
 
 impl FieldChanges {
@@ -221,6 +299,19 @@ impl FieldChanges {
         Self::default()
     }
 
+    /// Apply these changes to a field definition.
+    pub fn apply(&self, field: &mut Field) {
+        if let Some(ref field_type) = self.field_type {
+            field.field_type = field_type.clone();
+        }
+        if let Some(nullable) = self.nullable {
+            field.nullable = nullable;
+        }
+        if let Some(ref default) = self.default {
+            field.default = default.clone();
+        }
+    }
+
     pub fn set_type(mut self, field_type: FieldType) -> Self {
         self.field_type = Some(field_type);
         self
@@ -231,16 +322,16 @@ impl FieldChanges {
         self
     }
 
-    pub fn set_default(mut self, default: Option<String>) -> Self {
+    pub fn set_default(mut self, default: Option<DefaultValue>) -> Self {
         self.default = Some(default);
         self
     }
 }
 
-fn field_to_column_def(field: &Field) -> ColumnDef {
+fn field_to_column_def<B: Backend + ?Sized>(backend: &B, field: &Field) -> ColumnDef {
     let mut col = ColumnDef::new(Alias::new(&field.name));
 
-    apply_column_type(&mut col, &field.field_type);
+    apply_column_type(backend, &mut col, &field.name, &field.field_type);
 
     if field.primary_key {
         col.primary_key();
@@ -258,13 +349,51 @@ fn field_to_column_def(field: &Field) -> ColumnDef {
     }
 
     if let Some(ref default) = field.default {
-        col.default(sea_query::Expr::cust(default));
+        col.default(sea_query::Expr::cust(default.to_sql(backend)));
     }
 
     col
 }
 
-fn apply_column_type(col: &mut ColumnDef, field_type: &FieldType) {
+/// Comma-separated string literals, e.g. for `IN (...)` lists.
+pub(crate) fn string_list<B: Backend + ?Sized>(backend: &B, values: &[String]) -> String {
+    values
+        .iter()
+        .map(|value| backend.string_literal(value))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Types only PostgreSQL has a column type for.
+pub(crate) fn postgres_only(field_type: &FieldType) -> bool {
+    matches!(
+        field_type,
+        FieldType::Array(_)
+            | FieldType::Interval
+            | FieldType::TimeTz
+            | FieldType::Inet
+            | FieldType::Cidr
+            | FieldType::MacAddr
+            | FieldType::Xml
+    )
+}
+
+fn apply_column_type<B: Backend + ?Sized>(
+    backend: &B,
+    col: &mut ColumnDef,
+    column: &str,
+    field_type: &FieldType,
+) {
+    if let Some(sql_type) = backend.column_type(field_type) {
+        col.custom(Alias::new(sql_type));
+        return;
+    }
+    if !backend.supports_field_type(field_type) {
+        if let Some(fallback) = field_type.fallback() {
+            return apply_column_type(backend, col, column, &fallback);
+        }
+    }
+
     match field_type {
         FieldType::Serial => {
             col.integer();
@@ -323,6 +452,64 @@ fn apply_column_type(col: &mut ColumnDef, field_type: &FieldType) {
         FieldType::Decimal { precision, scale } => {
             col.decimal_len(*precision as u32, *scale as u32);
         }
+        FieldType::Enum { name, variants } => {
+            backend.enum_column(col, column, name, variants);
+        }
+        FieldType::Array(element) => {
+            let mut element_col = ColumnDef::new(Alias::new(column));
+            apply_column_type(backend, &mut element_col, column, element);
+            if let Some(element_type) = element_col.get_column_type() {
+                col.array(element_type.clone());
+            }
+        }
+        FieldType::Interval => {
+            col.interval(None, None);
+        }
+        FieldType::TimeTz => {
+            col.custom(Alias::new("time with time zone"));
+        }
+        FieldType::Inet => {
+            col.inet();
+        }
+        FieldType::Cidr => {
+            col.cidr();
+        }
+        FieldType::MacAddr => {
+            col.mac_address();
+        }
+        FieldType::Char(len) => {
+            col.char_len(*len as u32);
+        }
+        FieldType::TinyInt => {
+            col.tiny_integer();
+        }
+        FieldType::UnsignedTinyInt => {
+            col.tiny_unsigned();
+        }
+        FieldType::UnsignedSmallInt => {
+            col.small_unsigned();
+        }
+        FieldType::UnsignedInteger => {
+            col.unsigned();
+        }
+        FieldType::UnsignedBigInt => {
+            col.big_unsigned();
+        }
+        FieldType::Money => {
+            col.money();
+        }
+        FieldType::Xml => {
+            col.custom(Alias::new("xml"));
+        }
+        FieldType::FixedBinary(len) => {
+            col.binary_len(*len as u32);
+        }
+        FieldType::VarBinary(len) => {
+            col.var_binary(*len as u32);
+        }
+        FieldType::Custom(custom) => {
+            col.custom(Alias::new(custom.sql_type(backend.name())));
+        }
     }
 }
 
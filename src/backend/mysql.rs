@@ -1,9 +1,12 @@
 use sea_query::{
-    IndexCreateStatement, IndexDropStatement, MysqlQueryBuilder, TableAlterStatement,
-    TableCreateStatement, TableDropStatement, TableRenameStatement,
+    inject_parameters, Alias, ColumnDef, DeleteStatement, IndexCreateStatement, IndexDropStatement,
+    InsertStatement, MysqlQueryBuilder, TableAlterStatement, TableCreateStatement,
+    TableDropStatement, TableRenameStatement, Value,
 };
 
-use crate::backend::Backend;
+use crate::backend::{postgres_only, string_list, Backend};
+use crate::field::FieldType;
+use crate::operation::ScriptSyntax;
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct MySql;
@@ -30,6 +33,10 @@ impl Backend for MySql {
         false
     }
 
+    fn supports_field_type(&self, field_type: &FieldType) -> bool {
+        !postgres_only(field_type) && *field_type != FieldType::Money
+    }
+
     fn build_table_create(&self, stmt: TableCreateStatement) -> String {
         stmt.to_string(MysqlQueryBuilder)
     }
@@ -54,6 +61,32 @@ impl Backend for MySql {
         stmt.to_string(MysqlQueryBuilder)
     }
 
+    fn build_insert(&self, stmt: InsertStatement) -> String {
+        stmt.to_string(MysqlQueryBuilder)
+    }
+
+    fn build_delete(&self, stmt: DeleteStatement) -> String {
+        stmt.to_string(MysqlQueryBuilder)
+    }
+
+    fn inline_values(&self, sql: &str, values: &[Value]) -> String {
+        inject_parameters(sql, values.iter().cloned(), &MysqlQueryBuilder)
+    }
+
+    fn script_syntax(&self) -> ScriptSyntax {
+        ScriptSyntax {
+            backslash_escapes: true,
+            hash_comments: true,
+            delimiter_command: true,
+            routine_bodies: true,
+            ..ScriptSyntax::default()
+        }
+    }
+
+    fn enum_column(&self, col: &mut ColumnDef, _column: &str, _name: &str, variants: &[String]) {
+        col.custom(Alias::new(format!("ENUM({})", string_list(self, variants))));
+    }
+
     fn drop_constraint_sql(&self, table: &str, constraint_name: &str) -> String {
         // MySQL uses DROP INDEX for most constraints, DROP FOREIGN KEY for FKs
         // This is a simplified version - in practice you'd need to know the constraint type
@@ -198,4 +231,15 @@ mod tests {
         assert_eq!(backend.quote_identifier("users"), "`users`");
         assert_eq!(backend.quote_identifier("user`name"), "`user``name`");
     }
+
+    #[test]
+    fn mysql_extended_types() {
+        let column = |field_type| MySql.add_field_sql("t", &Field::new("c", field_type))[0].clone();
+        assert!(column(FieldType::UnsignedInteger).ends_with("int UNSIGNED"));
+        assert!(column(FieldType::TinyInt).ends_with("tinyint"));
+        assert!(column(FieldType::FixedBinary(16)).ends_with("binary(16)"));
+        assert!(column(FieldType::array(FieldType::Integer)).ends_with("json"));
+        assert!(column(FieldType::MacAddr).ends_with("varchar(17)"));
+        assert!(column(FieldType::Money).ends_with("decimal(19, 4)"));
+    }
 }
@@ -1,9 +1,12 @@
 use sea_query::{
-    IndexCreateStatement, IndexDropStatement, PostgresQueryBuilder, TableAlterStatement,
-    TableCreateStatement, TableDropStatement, TableRenameStatement,
+    inject_parameters, Alias, ColumnDef, DeleteStatement, IndexCreateStatement, IndexDropStatement,
+    InsertStatement, PostgresQueryBuilder, TableAlterStatement, TableCreateStatement,
+    TableDropStatement, TableRenameStatement, Value,
 };
 
 use crate::backend::Backend;
+use crate::field::FieldType;
+use crate::operation::ScriptSyntax;
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Postgres;
@@ -29,6 +32,20 @@ impl Backend for Postgres {
         true
     }
 
+    fn supports_enum_types(&self) -> bool {
+        true
+    }
+
+    fn supports_field_type(&self, field_type: &FieldType) -> bool {
+        !matches!(
+            field_type,
+            FieldType::UnsignedTinyInt
+                | FieldType::UnsignedSmallInt
+                | FieldType::UnsignedInteger
+                | FieldType::UnsignedBigInt
+        )
+    }
+
     fn build_table_create(&self, stmt: TableCreateStatement) -> String {
         stmt.to_string(PostgresQueryBuilder)
     }
@@ -53,6 +70,34 @@ impl Backend for Postgres {
         stmt.to_string(PostgresQueryBuilder)
     }
 
+    fn build_insert(&self, stmt: InsertStatement) -> String {
+        stmt.to_string(PostgresQueryBuilder)
+    }
+
+    fn build_delete(&self, stmt: DeleteStatement) -> String {
+        stmt.to_string(PostgresQueryBuilder)
+    }
+
+    fn placeholder(&self, n: usize) -> String {
+        format!("${}", n)
+    }
+
+    fn inline_values(&self, sql: &str, values: &[Value]) -> String {
+        inject_parameters(sql, values.iter().cloned(), &PostgresQueryBuilder)
+    }
+
+    fn script_syntax(&self) -> ScriptSyntax {
+        ScriptSyntax {
+            dollar_quotes: true,
+            nested_comments: true,
+            ..ScriptSyntax::default()
+        }
+    }
+
+    fn enum_column(&self, col: &mut ColumnDef, _column: &str, name: &str, _variants: &[String]) {
+        col.custom(Alias::new(self.quote_identifier(name)));
+    }
+
     fn drop_constraint_sql(&self, table: &str, constraint_name: &str) -> String {
         format!(
             "ALTER TABLE \"{}\" DROP CONSTRAINT \"{}\"",
@@ -198,4 +243,17 @@ mod tests {
         assert_eq!(backend.quote_identifier("users"), "\"users\"");
         assert_eq!(backend.quote_identifier("user\"name"), "\"user\"\"name\"");
     }
+
+    #[test]
+    fn postgres_extended_types() {
+        let column =
+            |field_type| Postgres.add_field_sql("t", &Field::new("c", field_type))[0].clone();
+        assert!(column(FieldType::array(FieldType::VarChar(16))).ends_with("varchar(16)[]"));
+        assert!(column(FieldType::TimeTz).ends_with("time with time zone"));
+        assert!(column(FieldType::Inet).ends_with("inet"));
+        assert!(column(FieldType::Money).ends_with("money"));
+        // No unsigned integers; widened to keep the full range.
+        assert!(column(FieldType::UnsignedInteger).ends_with("bigint"));
+        assert!(column(FieldType::UnsignedBigInt).ends_with("decimal(20, 0)"));
+    }
 }
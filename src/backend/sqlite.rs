@@ -1,9 +1,12 @@
 use sea_query::{
-    IndexCreateStatement, IndexDropStatement, SqliteQueryBuilder, TableAlterStatement,
-    TableCreateStatement, TableDropStatement, TableRenameStatement,
+    inject_parameters, DeleteStatement, IndexCreateStatement, IndexDropStatement, InsertStatement,
+    SqliteQueryBuilder, TableAlterStatement, TableCreateStatement, TableDropStatement,
+    TableRenameStatement, Value,
 };
 
-use crate::backend::Backend;
+use crate::backend::{postgres_only, Backend};
+use crate::field::FieldType;
+use crate::operation::ScriptSyntax;
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Sqlite;
@@ -29,6 +32,19 @@ impl Backend for Sqlite {
         true
     }
 
+    fn supports_field_type(&self, field_type: &FieldType) -> bool {
+        !postgres_only(field_type)
+    }
+
+    /// sea-query renders decimals as `real` here, which loses exactness and
+    /// can't take money's precision, so money is spelled as `numeric`.
+    fn column_type(&self, field_type: &FieldType) -> Option<String> {
+        match field_type {
+            FieldType::Money => Some("numeric(19, 4)".to_string()),
+            _ => None,
+        }
+    }
+
     fn build_table_create(&self, stmt: TableCreateStatement) -> String {
         stmt.to_string(SqliteQueryBuilder)
     }
@@ -53,6 +69,25 @@ impl Backend for Sqlite {
         stmt.to_string(SqliteQueryBuilder)
     }
 
+    fn build_insert(&self, stmt: InsertStatement) -> String {
+        stmt.to_string(SqliteQueryBuilder)
+    }
+
+    fn build_delete(&self, stmt: DeleteStatement) -> String {
+        stmt.to_string(SqliteQueryBuilder)
+    }
+
+    fn inline_values(&self, sql: &str, values: &[Value]) -> String {
+        inject_parameters(sql, values.iter().cloned(), &SqliteQueryBuilder)
+    }
+
+    fn script_syntax(&self) -> ScriptSyntax {
+        ScriptSyntax {
+            trigger_bodies: true,
+            ..ScriptSyntax::default()
+        }
+    }
+
     fn drop_constraint_sql(&self, _table: &str, constraint_name: &str) -> String {
         // SQLite doesn't support DROP CONSTRAINT, but indexes can be dropped
         format!(
@@ -250,4 +285,15 @@ mod tests {
         assert!(sql.contains("DROP INDEX"));
         assert!(sql.contains("\"uq_email\""));
     }
+
+    #[test]
+    fn sqlite_extended_types() {
+        let column =
+            |field_type| Sqlite.add_field_sql("t", &Field::new("c", field_type))[0].clone();
+        assert!(column(FieldType::array(FieldType::Integer)).ends_with("json_text"));
+        assert!(column(FieldType::Interval).ends_with("text"));
+        assert!(column(FieldType::Cidr).ends_with("varchar(49)"));
+        assert!(column(FieldType::Char(2)).ends_with("char(2)"));
+        assert!(column(FieldType::Money).ends_with("numeric(19, 4)"));
+    }
 }
@@ -0,0 +1,586 @@
+//! Declarative migration files.
+//!
+//! Simple schema changes can be authored as TOML, YAML or JSON files and
+//! loaded into a [`MigrationRegistry`] at runtime, without recompiling:
+//!
+//! ```toml
+//! dependencies = ["0001_create_users"]
+//!
+//! [[operations]]
+//! type = "add_field"
+//! table = "users"
+//! field = { name = "nickname", type = { varchar = 64 } }
+//! ```
+//!
+//! The migration name defaults to the file stem (`0002_add_nickname.toml`
+//! becomes `0002_add_nickname`) unless the file sets `name` explicitly.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::backend::FieldChanges;
+use crate::field::Field;
+use crate::migration::{Migration, MigrationRegistry};
+use crate::operation::{
+    AddConstraint, AddField, AddIndex, AlterField, Constraint, CreateTable, Index, Operation,
+    RunSql,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MigrationFile {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub run_before: Vec<String>,
+    #[serde(default = "default_atomic")]
+    pub atomic: bool,
+    #[serde(default)]
+    pub operations: Vec<OperationSpec>,
+}
+
+fn default_atomic() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum OperationSpec {
+    CreateTable {
+        name: String,
+        fields: Vec<Field>,
+    },
+    AddField {
+        table: String,
+        field: Field,
+    },
+    AlterField {
+        table: String,
+        field: String,
+        changes: FieldChanges,
+        #[serde(default)]
+        reverse: Option<FieldChanges>,
+    },
+    AddIndex {
+        table: String,
+        index: Index,
+    },
+    AddConstraint {
+        table: String,
+        constraint: Constraint,
+    },
+    RunSql {
+        sql: SqlSpec,
+        #[serde(default)]
+        reverse: Option<SqlSpec>,
+        #[serde(default)]
+        description: Option<String>,
+    },
+}
+
+/// SQL for a `run_sql` operation: either one statement for every backend,
+/// or a table of statements keyed by backend name (`postgres`, `sqlite`, ...).
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum SqlSpec {
+    All(String),
+    ByBackend(HashMap<String, String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl FileFormat {
+    /// Detect the format from a file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "toml" => Some(FileFormat::Toml),
+            "yaml" | "yml" => Some(FileFormat::Yaml),
+            "json" => Some(FileFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+impl MigrationFile {
+    pub fn parse(contents: &str, format: FileFormat) -> Result<Self, String> {
+        match format {
+            FileFormat::Toml => toml::from_str(contents).map_err(|e| e.to_string()),
+            FileFormat::Yaml => serde_yaml::from_str(contents).map_err(|e| e.to_string()),
+            FileFormat::Json => serde_json::from_str(contents).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Build a [`Migration`], using `default_name` when the file has no `name`.
+    pub fn into_migration(self, default_name: &str) -> Result<Migration, String> {
+        let name = self.name.unwrap_or_else(|| default_name.to_string());
+        let operations = self
+            .operations
+            .into_iter()
+            .map(OperationSpec::into_operation)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut migration = Migration::new(name)
+            .dependencies(self.dependencies)
+            .atomic(self.atomic)
+            .forward_ops(operations);
+        migration
+            .run_before
+            .extend(self.run_before.into_iter().map(Cow::Owned));
+        Ok(migration)
+    }
+}
+
+impl OperationSpec {
+    pub fn into_operation(self) -> Result<Box<dyn Operation>, String> {
+        let op: Box<dyn Operation> = match self {
+            OperationSpec::CreateTable { name, fields } => Box::new(CreateTable { name, fields }),
+            OperationSpec::AddField { table, field } => Box::new(AddField::new(table, field)),
+            OperationSpec::AlterField {
+                table,
+                field,
+                changes,
+                reverse,
+            } => {
+                let mut op = AlterField::new(table, field);
+                op.changes = changes;
+                op.reverse_changes = reverse;
+                Box::new(op)
+            }
+            OperationSpec::AddIndex { table, index } => Box::new(AddIndex::new(table, index)),
+            OperationSpec::AddConstraint { table, constraint } => {
+                Box::new(AddConstraint::new(table, constraint))
+            }
+            OperationSpec::RunSql {
+                sql,
+                reverse,
+                description,
+            } => {
+                let op = run_sql(sql, reverse)?;
+                match description {
+                    Some(description) => Box::new(op.with_description(description)),
+                    None => Box::new(op),
+                }
+            }
+        };
+        Ok(op)
+    }
+}
+
+/// Backend names `run_sql` tables may be keyed by.
+const BACKENDS: [&str; 3] = ["postgres", "mysql", "sqlite"];
+
+fn check_backends(spec: &SqlSpec) -> Result<(), String> {
+    let SqlSpec::ByBackend(map) = spec else {
+        return Ok(());
+    };
+    match map
+        .keys()
+        .filter(|key| !BACKENDS.contains(&key.as_str()))
+        .min()
+    {
+        Some(key) => Err(format!(
+            "run_sql: unknown backend '{}' (expected one of {})",
+            key,
+            BACKENDS.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+fn run_sql(sql: SqlSpec, reverse: Option<SqlSpec>) -> Result<RunSql, String> {
+    check_backends(&sql)?;
+    if let Some(ref reverse) = reverse {
+        check_backends(reverse)?;
+    }
+    match (sql, reverse) {
+        (SqlSpec::All(sql), None) => Ok(RunSql::new(sql)),
+        (SqlSpec::All(sql), Some(SqlSpec::All(reverse))) => Ok(RunSql::reversible(sql, reverse)),
+        (SqlSpec::ByBackend(map), None) => Ok(sorted(map)
+            .into_iter()
+            .fold(RunSql::portable(), |op, (backend, sql)| {
+                op.for_backend(&backend, sql)
+            })),
+        (SqlSpec::ByBackend(map), Some(SqlSpec::ByBackend(mut reverse))) => sorted(map)
+            .into_iter()
+            .try_fold(RunSql::portable(), |op, (backend, sql)| {
+                let backward = reverse
+                    .remove(&backend)
+                    .ok_or_else(|| format!("run_sql: no reverse SQL for backend '{}'", backend))?;
+                Ok(op.for_backend_reversible(&backend, sql, backward))
+            }),
+        _ => Err("run_sql: sql and reverse must both be plain or both per-backend".to_string()),
+    }
+}
+
+fn sorted(map: HashMap<String, String>) -> Vec<(String, String)> {
+    let mut entries: Vec<_> = map.into_iter().collect();
+    entries.sort();
+    entries
+}
+
+/// Load a single migration file. The format is detected from the extension.
+pub fn load_file(path: &Path) -> Result<Migration, LoadError> {
+    let format = FileFormat::from_path(path).ok_or_else(|| LoadError::Invalid {
+        path: path.to_path_buf(),
+        error: "unsupported file extension".to_string(),
+    })?;
+
+    let contents = fs::read_to_string(path).map_err(|e| LoadError::Io {
+        path: path.to_path_buf(),
+        error: e.to_string(),
+    })?;
+
+    let file = MigrationFile::parse(&contents, format).map_err(|error| LoadError::Parse {
+        path: path.to_path_buf(),
+        error,
+    })?;
+
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+
+    file.into_migration(stem)
+        .map_err(|error| LoadError::Invalid {
+            path: path.to_path_buf(),
+            error,
+        })
+}
+
+/// Load every `.toml`, `.yaml`, `.yml` and `.json` file in a directory
+/// into a new registry. Other files are ignored.
+pub fn load_dir(dir: &Path) -> Result<MigrationRegistry, LoadError> {
+    let mut registry = MigrationRegistry::new();
+    load_dir_into(&mut registry, dir)?;
+    Ok(registry)
+}
+
+/// Load a directory of migration files into an existing registry.
+/// Files are registered in file name order.
+pub fn load_dir_into(registry: &mut MigrationRegistry, dir: &Path) -> Result<(), LoadError> {
+    let io_error = |e: std::io::Error| LoadError::Io {
+        path: dir.to_path_buf(),
+        error: e.to_string(),
+    };
+
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        if path.is_file() && FileFormat::from_path(&path).is_some() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    for path in paths {
+        registry.register(load_file(&path)?);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoadError {
+    Io { path: PathBuf, error: String },
+    Parse { path: PathBuf, error: String },
+    Invalid { path: PathBuf, error: String },
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadError::Io { path, error } => {
+                write!(f, "Failed to read {}: {}", path.display(), error)
+            }
+            LoadError::Parse { path, error } => {
+                write!(f, "Failed to parse {}: {}", path.display(), error)
+            }
+            LoadError::Invalid { path, error } => {
+                write!(f, "Invalid migration {}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Postgres, Sqlite};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cetane_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parse_toml_create_table() {
+        let file = MigrationFile::parse(
+            r#"
+            [[operations]]
+            type = "create_table"
+            name = "users"
+            fields = [
+                { name = "id", type = "serial", primary_key = true },
+                { name = "email", type = { varchar = 255 }, nullable = false, unique = true },
+                { name = "active", type = "boolean", default = { bool = true } },
+                { name = "joined_at", type = "timestamp", default = "current_timestamp" },
+                { name = "tags", type = { array = { varchar = 32 } } },
+            ]
+            "#,
+            FileFormat::Toml,
+        )
+        .unwrap();
+
+        let migration = file.into_migration("0001_create_users").unwrap();
+        assert_eq!(migration.name, "0001_create_users");
+        assert!(migration.is_atomic());
+
+        let sql = migration.forward_sql(&Sqlite);
+        assert!(sql[0].contains("CREATE TABLE \"users\""));
+        assert!(sql[0].contains("\"email\" varchar(255) NOT NULL UNIQUE"));
+        assert!(sql[0].contains("\"active\" boolean DEFAULT 1"));
+        assert!(sql[0].contains("DEFAULT CURRENT_TIMESTAMP"));
+        assert!(sql[0].contains("\"tags\" json_text"));
+        assert!(migration.forward_sql(&Postgres)[0].contains("\"tags\" varchar(32)[]"));
+    }
+
+    #[test]
+    fn parse_yaml_with_dependencies_and_atomic() {
+        let file = MigrationFile::parse(
+            r#"
+name: 0002_index_email
+dependencies: [0001_create_users]
+run_before: [0003_backfill]
+atomic: false
+operations:
+  - type: add_index
+    table: users
+    index:
+      name: idx_users_email
+      columns: [email, [created_at, desc]]
+      unique: true
+"#,
+            FileFormat::Yaml,
+        )
+        .unwrap();
+
+        let migration = file.into_migration("ignored").unwrap();
+        assert_eq!(migration.name, "0002_index_email");
+        assert_eq!(migration.dependencies, vec!["0001_create_users"]);
+        assert_eq!(migration.run_before, vec!["0003_backfill"]);
+        assert!(!migration.is_atomic());
+
+        let sql = migration.forward_sql(&Sqlite);
+        assert!(sql[0].contains("CREATE UNIQUE INDEX \"idx_users_email\""));
+        assert!(sql[0].contains("\"created_at\" DESC"));
+    }
+
+    #[test]
+    fn parse_json_alter_field_and_constraint() {
+        let file = MigrationFile::parse(
+            r#"{
+                "operations": [
+                    {
+                        "type": "alter_field",
+                        "table": "users",
+                        "field": "email",
+                        "changes": { "nullable": false, "default": null },
+                        "reverse": { "nullable": true }
+                    },
+                    {
+                        "type": "add_constraint",
+                        "table": "posts",
+                        "constraint": {
+                            "type": "foreign_key",
+                            "name": "fk_posts_user",
+                            "columns": ["user_id"],
+                            "ref_table": "users",
+                            "ref_columns": ["id"],
+                            "on_delete": "cascade"
+                        }
+                    }
+                ]
+            }"#,
+            FileFormat::Json,
+        )
+        .unwrap();
+
+        let migration = file.into_migration("0003").unwrap();
+        assert!(migration.is_reversible());
+
+        let sql = migration.forward_sql(&Postgres);
+        assert!(sql[0].contains("NOT NULL"));
+        assert!(sql[1].contains("ON DELETE CASCADE"));
+
+        let ops = migration.forward_operations();
+        assert_eq!(ops[0].describe(), "Alter field email on users");
+    }
+
+    #[test]
+    fn parse_run_sql_per_backend() {
+        let file = MigrationFile::parse(
+            r#"
+            [[operations]]
+            type = "run_sql"
+            sql = { postgres = "CREATE EXTENSION citext", sqlite = "SELECT 1" }
+            reverse = { postgres = "DROP EXTENSION citext", sqlite = "SELECT 1" }
+            description = "Enable citext"
+            "#,
+            FileFormat::Toml,
+        )
+        .unwrap();
+
+        let migration = file.into_migration("0004").unwrap();
+        assert!(migration.is_reversible());
+        assert_eq!(
+            migration.forward_sql(&Postgres),
+            vec!["CREATE EXTENSION citext"]
+        );
+        assert_eq!(
+            migration.backward_sql(&Postgres).unwrap(),
+            vec!["DROP EXTENSION citext"]
+        );
+        assert_eq!(
+            migration.forward_operations()[0].describe(),
+            "Enable citext"
+        );
+    }
+
+    #[test]
+    fn run_sql_mismatched_reverse_is_invalid() {
+        let file = MigrationFile::parse(
+            r#"
+            [[operations]]
+            type = "run_sql"
+            sql = { postgres = "ANALYZE" }
+            reverse = "SELECT 1"
+            "#,
+            FileFormat::Toml,
+        )
+        .unwrap();
+
+        assert!(file.into_migration("0005").is_err());
+    }
+
+    #[test]
+    fn unknown_operation_type_fails_to_parse() {
+        let result = MigrationFile::parse(
+            r#"
+            [[operations]]
+            type = "explode_table"
+            "#,
+            FileFormat::Toml,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn file_format_from_path() {
+        assert_eq!(
+            FileFormat::from_path(Path::new("a.toml")),
+            Some(FileFormat::Toml)
+        );
+        assert_eq!(
+            FileFormat::from_path(Path::new("a.yml")),
+            Some(FileFormat::Yaml)
+        );
+        assert_eq!(
+            FileFormat::from_path(Path::new("a.json")),
+            Some(FileFormat::Json)
+        );
+        assert_eq!(FileFormat::from_path(Path::new("a.sql")), None);
+    }
+
+    #[test]
+    fn load_dir_registers_all_formats() {
+        let dir = temp_dir("load_dir");
+        fs::write(
+            dir.join("0001_create_users.toml"),
+            r#"
+            [[operations]]
+            type = "create_table"
+            name = "users"
+            fields = [{ name = "id", type = "serial", primary_key = true }]
+            "#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("0002_add_email.yaml"),
+            "dependencies: [0001_create_users]\noperations:\n  - type: add_field\n    table: users\n    field: { name: email, type: text }\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("0003_noop.json"),
+            r#"{ "dependencies": ["0002_add_email"] }"#,
+        )
+        .unwrap();
+        fs::write(dir.join("README.md"), "not a migration").unwrap();
+
+        let registry = load_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(registry.len(), 3);
+        assert_eq!(
+            registry.resolve_order().unwrap(),
+            vec!["0001_create_users", "0002_add_email", "0003_noop"]
+        );
+    }
+
+    #[test]
+    fn load_file_reports_parse_errors_with_path() {
+        let dir = temp_dir("load_error");
+        let path = dir.join("0001_broken.toml");
+        fs::write(&path, "operations = 5").unwrap();
+
+        let result = load_file(&path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        match result {
+            Err(LoadError::Parse {
+                path: error_path, ..
+            }) => assert_eq!(error_path, path),
+            other => panic!("Expected parse error, got {:?}", other.map(|m| m.name)),
+        }
+    }
+
+    #[test]
+    fn load_file_rejects_unknown_run_sql_backends() {
+        let dir = temp_dir("unknown_backend");
+        let path = dir.join("0001_extension.toml");
+        fs::write(
+            &path,
+            r#"
+            [[operations]]
+            type = "run_sql"
+            sql = { postgres = "CREATE EXTENSION citext", postgresql = "SELECT 1" }
+            "#,
+        )
+        .unwrap();
+
+        let result = load_file(&path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        match result {
+            Err(LoadError::Invalid { error, .. }) => assert_eq!(
+                error,
+                "run_sql: unknown backend 'postgresql' (expected one of postgres, mysql, sqlite)"
+            ),
+            other => panic!("Expected invalid error, got {:?}", other.map(|m| m.name)),
+        }
+    }
+}
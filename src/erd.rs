@@ -0,0 +1,284 @@
+//! Entity-relationship diagrams from a replayed [`SchemaState`].
+//!
+//! Tables are drawn with their columns, backend-neutral types and key
+//! markers (`PK`, `FK`, `UK`); foreign keys become edges from the
+//! referencing table to the referenced one.
+
+use crate::field::Field;
+use crate::migration::{MigrationError, MigrationRegistry};
+use crate::schema::{SchemaState, TableState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErFormat {
+    /// Mermaid `erDiagram`.
+    Mermaid,
+    PlantUml,
+    Dot,
+}
+
+/// Replay the registry and render the resulting schema.
+pub fn render_registry(
+    registry: &MigrationRegistry,
+    format: ErFormat,
+) -> Result<String, MigrationError> {
+    Ok(render(&SchemaState::from_registry(registry)?, format))
+}
+
+pub fn render(schema: &SchemaState, format: ErFormat) -> String {
+    match format {
+        ErFormat::Mermaid => render_mermaid(schema),
+        ErFormat::PlantUml => render_plantuml(schema),
+        ErFormat::Dot => render_dot(schema),
+    }
+}
+
+fn keys(table: &TableState, field: &Field) -> Vec<&'static str> {
+    let mut keys = Vec::new();
+    if field.primary_key {
+        keys.push("PK");
+    }
+    let is_foreign = table
+        .relations()
+        .iter()
+        .any(|relation| relation.columns.contains(&field.name));
+    if is_foreign {
+        keys.push("FK");
+    }
+    if field.unique {
+        keys.push("UK");
+    }
+    keys
+}
+
+fn render_mermaid(schema: &SchemaState) -> String {
+    let mut out = String::from("erDiagram\n");
+    for table in schema.tables() {
+        out.push_str(&format!("    {} {{\n", table.name));
+        for field in &table.fields {
+            // Mermaid attribute types can't contain commas or spaces.
+            let field_type = field.field_type.to_string().replace([',', ' '], "_");
+            let keys = keys(table, field);
+            if keys.is_empty() {
+                out.push_str(&format!("        {} {}\n", field_type, field.name));
+            } else {
+                out.push_str(&format!(
+                    "        {} {} {}\n",
+                    field_type,
+                    field.name,
+                    keys.join(", ")
+                ));
+            }
+        }
+        out.push_str("    }\n");
+    }
+    for table in schema.tables() {
+        for relation in table.relations() {
+            out.push_str(&format!(
+                "    {} ||--o{{ {} : \"{}\"\n",
+                relation.ref_table,
+                table.name,
+                relation.columns.join(", ")
+            ));
+        }
+    }
+    out
+}
+
+fn render_plantuml(schema: &SchemaState) -> String {
+    let mut out = String::from("@startuml\n");
+    for table in schema.tables() {
+        out.push_str(&format!("entity {} {{\n", table.name));
+        let (keyed, rest): (Vec<&Field>, Vec<&Field>) =
+            table.fields.iter().partition(|f| f.primary_key);
+        let line = |field: &Field| {
+            let stereotypes: String = keys(table, field)
+                .iter()
+                .map(|key| format!(" <<{}>>", key))
+                .collect();
+            format!(
+                "  {}{} : {}{}\n",
+                if field.nullable { "" } else { "* " },
+                field.name,
+                field.field_type,
+                stereotypes
+            )
+        };
+        keyed.into_iter().for_each(|f| out.push_str(&line(f)));
+        out.push_str("  --\n");
+        rest.into_iter().for_each(|f| out.push_str(&line(f)));
+        out.push_str("}\n");
+    }
+    for table in schema.tables() {
+        for relation in table.relations() {
+            out.push_str(&format!(
+                "{} }}o--|| {} : {}\n",
+                table.name,
+                relation.ref_table,
+                relation.columns.join(", ")
+            ));
+        }
+    }
+    out.push_str("@enduml\n");
+    out
+}
+
+fn render_dot(schema: &SchemaState) -> String {
+    // Record labels treat these characters as structure.
+    let escape = |s: &str| {
+        s.chars().fold(String::new(), |mut out, c| {
+            if matches!(c, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+                out.push('\\');
+            }
+            out.push(c);
+            out
+        })
+    };
+
+    let mut out = String::from("digraph schema {\n    rankdir=LR;\n    node [shape=record];\n");
+    for table in schema.tables() {
+        let columns: String = table
+            .fields
+            .iter()
+            .map(|field| {
+                let keys = keys(table, field);
+                let keys = if keys.is_empty() {
+                    String::new()
+                } else {
+                    format!(" ({})", keys.join(", "))
+                };
+                format!(
+                    "{} : {}{}\\l",
+                    escape(&field.name),
+                    escape(&field.field_type.to_string()),
+                    keys
+                )
+            })
+            .collect();
+        out.push_str(&format!(
+            "    \"{}\" [label=\"{{{}|{}}}\"];\n",
+            escape(&table.name),
+            escape(&table.name),
+            columns
+        ));
+    }
+    for table in schema.tables() {
+        for relation in table.relations() {
+            out.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                escape(&table.name),
+                escape(&relation.ref_table),
+                escape(&relation.columns.join(", "))
+            ));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::FieldType;
+    use crate::migration::Migration;
+    use crate::operation::{AddConstraint, Constraint, CreateTable};
+
+    fn setup_registry() -> MigrationRegistry {
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_initial")
+                .operation(
+                    CreateTable::new("orgs")
+                        .add_field(Field::new("id", FieldType::Serial).primary_key())
+                        .add_field(
+                            Field::new(
+                                "balance",
+                                FieldType::Decimal {
+                                    precision: 10,
+                                    scale: 2,
+                                },
+                            )
+                            .not_null(),
+                        ),
+                )
+                .operation(
+                    CreateTable::new("users")
+                        .add_field(Field::new("id", FieldType::Serial).primary_key())
+                        .add_field(Field::new("email", FieldType::VarChar(255)).unique())
+                        .add_field(Field::new("org_id", FieldType::Integer)),
+                ),
+        );
+        registry.register(
+            Migration::new("0002_fk")
+                .depends_on(&["0001_initial"])
+                .operation(AddConstraint::new(
+                    "users",
+                    Constraint::foreign_key(
+                        "fk_users_org",
+                        vec!["org_id".to_string()],
+                        "orgs",
+                        vec!["id".to_string()],
+                    ),
+                )),
+        );
+        registry
+    }
+
+    #[test]
+    fn mermaid_er_diagram() {
+        let diagram = render_registry(&setup_registry(), ErFormat::Mermaid).unwrap();
+
+        assert_eq!(
+            diagram,
+            "erDiagram\n\
+             \x20   orgs {\n\
+             \x20       serial id PK\n\
+             \x20       decimal(10_2) balance\n\
+             \x20   }\n\
+             \x20   users {\n\
+             \x20       serial id PK\n\
+             \x20       varchar(255) email UK\n\
+             \x20       integer org_id FK\n\
+             \x20   }\n\
+             \x20   orgs ||--o{ users : \"org_id\"\n"
+        );
+    }
+
+    #[test]
+    fn plantuml_er_diagram() {
+        let diagram = render_registry(&setup_registry(), ErFormat::PlantUml).unwrap();
+
+        assert!(diagram.starts_with("@startuml\nentity orgs {\n  * id : serial <<PK>>\n  --\n"));
+        assert!(diagram.contains("  * balance : decimal(10,2)\n"));
+        assert!(diagram.contains("  email : varchar(255) <<UK>>\n"));
+        assert!(diagram.contains("  org_id : integer <<FK>>\n"));
+        assert!(diagram.contains("users }o--|| orgs : org_id\n"));
+        assert!(diagram.ends_with("@enduml\n"));
+    }
+
+    #[test]
+    fn dot_er_diagram() {
+        let diagram = render_registry(&setup_registry(), ErFormat::Dot).unwrap();
+
+        assert!(diagram.starts_with("digraph schema {\n"));
+        assert!(diagram.contains(
+            "    \"users\" [label=\"{users|id : serial (PK)\\lemail : varchar(255) (UK)\\lorg_id : integer (FK)\\l}\"];\n"
+        ));
+        assert!(diagram.contains("    \"users\" -> \"orgs\" [label=\"org_id\"];\n"));
+    }
+
+    #[test]
+    fn field_references_are_relations() {
+        let mut schema = SchemaState::new();
+        schema.apply(
+            &Migration::new("m").operation(
+                CreateTable::new("posts").add_field(
+                    Field::new("author_id", FieldType::Integer).references("users", "id"),
+                ),
+            ),
+        );
+
+        let diagram = render(&schema, ErFormat::Mermaid);
+        assert!(diagram.contains("integer author_id FK\n"));
+        assert!(diagram.contains("users ||--o{ posts : \"author_id\"\n"));
+    }
+}
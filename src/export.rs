@@ -0,0 +1,381 @@
+//! Export a registry as plain SQL files for external migration runners.
+//!
+//! Migrations are written in `resolve_order()`, numbered from 1, with the
+//! SQL generated for one [`Backend`]. Down files come from `try_backward_sql`
+//! and are only written for migrations that can be reversed on the backend.
+//! Migrations that run Rust code or can't run on the backend fail the export
+//! instead of producing partial files.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backend::Backend;
+use crate::migration::{Migration, MigrationError, MigrationRegistry};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// `V{n}__{name}.sql` (refinery has no down migrations).
+    Refinery,
+    /// `V{n}__{name}.sql` plus `U{n}__{name}.sql` undo files.
+    Flyway,
+    /// One `{n}_{name}.sql` file with `-- migrate:up` / `-- migrate:down` sections.
+    Dbmate,
+    /// `{n}_{name}.up.sql` and `{n}_{name}.down.sql`.
+    GolangMigrate,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportedFile {
+    pub file_name: String,
+    pub contents: String,
+}
+
+/// Render every migration in the registry for `backend` in the given layout.
+pub fn export(
+    registry: &MigrationRegistry,
+    backend: &dyn Backend,
+    format: ExportFormat,
+) -> Result<Vec<ExportedFile>, ExportError> {
+    let mut files = Vec::new();
+
+    let order = registry.resolve_order().map_err(ExportError::Migration)?;
+    for (index, name) in order.into_iter().enumerate() {
+        let migration = registry
+            .get(name)
+            .ok_or_else(|| ExportError::Migration(MigrationError::NotFound(name.to_string())))?;
+        files.extend(export_migration(migration, index + 1, backend, format)?);
+    }
+
+    Ok(files)
+}
+
+/// Render the registry and write the files into `dir`, creating it if needed.
+pub fn export_to_dir(
+    registry: &MigrationRegistry,
+    backend: &dyn Backend,
+    format: ExportFormat,
+    dir: &Path,
+) -> Result<Vec<PathBuf>, ExportError> {
+    let files = export(registry, backend, format)?;
+
+    fs::create_dir_all(dir).map_err(|e| ExportError::Io {
+        path: dir.to_path_buf(),
+        error: e.to_string(),
+    })?;
+
+    let mut paths = Vec::new();
+    for file in files {
+        let path = dir.join(&file.file_name);
+        fs::write(&path, &file.contents).map_err(|e| ExportError::Io {
+            path: path.clone(),
+            error: e.to_string(),
+        })?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+fn export_migration(
+    migration: &Migration,
+    version: usize,
+    backend: &dyn Backend,
+    format: ExportFormat,
+) -> Result<Vec<ExportedFile>, ExportError> {
+    let qualified_name = migration.qualified_name();
+    if migration.runs_code() {
+        return Err(ExportError::RunsCode(qualified_name.into_owned()));
+    }
+    migration
+        .check(backend)
+        .map_err(|error| ExportError::Unsupported {
+            migration: qualified_name.to_string(),
+            error,
+        })?;
+
+    let name = file_safe(&qualified_name);
+    let up = join_statements(&migration.forward_sql(backend));
+    let down = migration
+        .try_backward_sql(backend)
+        .map(|sqls| join_statements(&sqls));
+
+    let file = |file_name: String, contents: String| ExportedFile {
+        file_name,
+        contents,
+    };
+
+    let files = match format {
+        ExportFormat::Refinery => vec![file(format!("V{}__{}.sql", version, name), up)],
+        ExportFormat::Flyway => {
+            let mut files = vec![file(format!("V{}__{}.sql", version, name), up)];
+            if let Some(down) = down {
+                files.push(file(format!("U{}__{}.sql", version, name), down));
+            }
+            files
+        }
+        ExportFormat::Dbmate => {
+            let options = if migration.is_atomic() {
+                ""
+            } else {
+                " transaction:false"
+            };
+            let contents = format!(
+                "-- migrate:up{}\n{}\n-- migrate:down{}\n{}",
+                options,
+                up,
+                options,
+                down.unwrap_or_default()
+            );
+            vec![file(format!("{}_{}.sql", version, name), contents)]
+        }
+        ExportFormat::GolangMigrate => {
+            let mut files = vec![file(format!("{}_{}.up.sql", version, name), up)];
+            if let Some(down) = down {
+                files.push(file(format!("{}_{}.down.sql", version, name), down));
+            }
+            files
+        }
+    };
+    Ok(files)
+}
+
+fn join_statements(sqls: &[String]) -> String {
+    sqls.iter()
+        .map(|sql| {
+            let sql = sql.trim_end();
+            if sql.ends_with(';') {
+                format!("{}\n", sql)
+            } else {
+                format!("{};\n", sql)
+            }
+        })
+        .collect()
+}
+
+/// Runners parse version and name out of the file name, so keep it to
+/// ASCII letters, digits and underscores.
+fn file_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportError {
+    Migration(MigrationError),
+    /// The migration runs Rust code (`RunCode`, a paused `Backfill`), which
+    /// has no SQL to write.
+    RunsCode(String),
+    /// The migration can't run on the backend.
+    Unsupported {
+        migration: String,
+        error: String,
+    },
+    Io {
+        path: PathBuf,
+        error: String,
+    },
+}
+
+impl std::fmt::Display for ExportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportError::Migration(error) => write!(f, "{}", error),
+            ExportError::RunsCode(migration) => write!(
+                f,
+                "Migration {} runs Rust code and can't be exported as SQL",
+                migration
+            ),
+            ExportError::Unsupported { migration, error } => {
+                write!(f, "Migration {} can't be exported: {}", migration, error)
+            }
+            ExportError::Io { path, error } => {
+                write!(f, "Failed to write {}: {}", path.display(), error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Postgres, Sqlite};
+    use crate::field::{Field, FieldType};
+    use crate::operation::{AddIndex, CreateTable, Index, RunCode, RunSql};
+
+    fn setup_registry() -> MigrationRegistry {
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0002_index_email")
+                .depends_on(&["0001_create_users"])
+                .atomic(false)
+                .operation(AddIndex::new(
+                    "users",
+                    Index::new("idx_users_email").column("email"),
+                )),
+        );
+        registry.register(
+            Migration::new("0001_create_users").operation(
+                CreateTable::new("users")
+                    .add_field(Field::new("id", FieldType::Serial).primary_key())
+                    .add_field(Field::new("email", FieldType::Text).not_null()),
+            ),
+        );
+        registry.register(
+            Migration::new("0003-backfill")
+                .depends_on(&["0002_index_email"])
+                .operation(RunSql::new("UPDATE users SET email = lower(email);")),
+        );
+        registry
+    }
+
+    fn names(files: &[ExportedFile]) -> Vec<&str> {
+        files.iter().map(|f| f.file_name.as_str()).collect()
+    }
+
+    #[test]
+    fn refinery_layout() {
+        let files = export(&setup_registry(), &Sqlite, ExportFormat::Refinery).unwrap();
+
+        assert_eq!(
+            names(&files),
+            vec![
+                "V1__0001_create_users.sql",
+                "V2__0002_index_email.sql",
+                "V3__0003_backfill.sql"
+            ]
+        );
+        assert!(files[0].contents.starts_with("CREATE TABLE \"users\""));
+        assert!(files[0].contents.ends_with(";\n"));
+        assert_eq!(
+            files[2].contents,
+            "UPDATE users SET email = lower(email);\n"
+        );
+    }
+
+    #[test]
+    fn flyway_layout_writes_undo_for_reversible_migrations() {
+        let files = export(&setup_registry(), &Sqlite, ExportFormat::Flyway).unwrap();
+
+        assert_eq!(
+            names(&files),
+            vec![
+                "V1__0001_create_users.sql",
+                "U1__0001_create_users.sql",
+                "V2__0002_index_email.sql",
+                "U2__0002_index_email.sql",
+                "V3__0003_backfill.sql"
+            ]
+        );
+        assert_eq!(files[1].contents, "DROP TABLE \"users\";\n");
+    }
+
+    #[test]
+    fn dbmate_layout_uses_sections() {
+        let files = export(&setup_registry(), &Postgres, ExportFormat::Dbmate).unwrap();
+
+        assert_eq!(
+            names(&files),
+            vec![
+                "1_0001_create_users.sql",
+                "2_0002_index_email.sql",
+                "3_0003_backfill.sql"
+            ]
+        );
+        assert!(files[0].contents.starts_with("-- migrate:up\nCREATE TABLE"));
+        assert!(files[0]
+            .contents
+            .ends_with("-- migrate:down\nDROP TABLE \"users\";\n"));
+        assert!(files[1]
+            .contents
+            .starts_with("-- migrate:up transaction:false\n"));
+        assert!(files[2].contents.ends_with("-- migrate:down\n"));
+    }
+
+    #[test]
+    fn golang_migrate_layout() {
+        let files = export(&setup_registry(), &Sqlite, ExportFormat::GolangMigrate).unwrap();
+
+        assert_eq!(
+            names(&files),
+            vec![
+                "1_0001_create_users.up.sql",
+                "1_0001_create_users.down.sql",
+                "2_0002_index_email.up.sql",
+                "2_0002_index_email.down.sql",
+                "3_0003_backfill.up.sql"
+            ]
+        );
+        assert!(files[3].contents.contains("DROP INDEX"));
+    }
+
+    #[test]
+    fn export_fails_on_missing_dependency() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("a").depends_on(&["missing"]));
+
+        let result = export(&registry, &Sqlite, ExportFormat::Refinery);
+        assert!(matches!(
+            result,
+            Err(ExportError::Migration(MigrationError::NotFound(_)))
+        ));
+    }
+
+    #[test]
+    fn export_refuses_migrations_it_cannot_render() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_code").operation(RunCode::new(|_| Ok(()))));
+        assert_eq!(
+            export(&registry, &Sqlite, ExportFormat::Refinery)
+                .unwrap_err()
+                .to_string(),
+            "Migration 0001_code runs Rust code and can't be exported as SQL"
+        );
+
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_pg_only")
+                .operation(RunSql::portable().for_backend("postgres", "SELECT 1")),
+        );
+        assert!(matches!(
+            export(&registry, &Sqlite, ExportFormat::Refinery),
+            Err(ExportError::Unsupported { ref migration, .. }) if migration == "0001_pg_only"
+        ));
+    }
+
+    #[test]
+    fn export_skips_down_files_missing_for_the_backend() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_view").operation(
+                RunSql::portable()
+                    .for_backend("sqlite", "CREATE VIEW v AS SELECT 1")
+                    .for_backend_reversible("postgres", "CREATE VIEW v AS SELECT 1", "DROP VIEW v"),
+            ),
+        );
+
+        let files = export(&registry, &Sqlite, ExportFormat::Flyway).unwrap();
+        assert_eq!(names(&files), vec!["V1__0001_view.sql"]);
+    }
+
+    #[test]
+    fn export_to_dir_writes_files() {
+        let dir = std::env::temp_dir().join(format!("cetane_export_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let paths = export_to_dir(
+            &setup_registry(),
+            &Sqlite,
+            ExportFormat::GolangMigrate,
+            &dir,
+        )
+        .unwrap();
+        let contents = fs::read_to_string(&paths[1]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(paths.len(), 5);
+        assert_eq!(contents, "DROP TABLE \"users\";\n");
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A column type that isn't a [`FieldType`](super::FieldType) variant, such
+/// as PostGIS `geometry(Point, 4326)`, `citext` or `vector(1536)`.
+///
+/// Use it with `FieldType::custom`. Two custom types are the same type when
+/// their names match, which is what schema comparisons see.
+pub trait CustomType: fmt::Debug + Send + Sync {
+    /// Identifies the type in migration files, diagrams and comparisons.
+    fn name(&self) -> String;
+
+    /// The column type on the backend called `backend` (see `Backend::name`).
+    fn sql_type(&self, backend: &str) -> String;
+}
+
+impl PartialEq for dyn CustomType {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+/// A [`CustomType`] spelled out as SQL: the name is the column type, except
+/// on backends given their own with [`SqlType::on`].
+///
+/// ```ignore
+/// FieldType::custom(SqlType::new("citext").on("sqlite", "text COLLATE NOCASE"))
+/// ```
+///
+/// Serialized custom types are restored as a `SqlType` carrying what they
+/// rendered on each built-in backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SqlType {
+    pub name: String,
+    /// Column types for specific backends, keyed by backend name.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub backends: BTreeMap<String, String>,
+}
+
+impl SqlType {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            backends: BTreeMap::new(),
+        }
+    }
+
+    pub fn on(mut self, backend: impl Into<String>, sql_type: impl Into<String>) -> Self {
+        self.backends.insert(backend.into(), sql_type.into());
+        self
+    }
+
+    /// Capture what `custom` renders on each built-in backend.
+    #[cfg(feature = "serde")]
+    fn of(custom: &dyn CustomType) -> Self {
+        let name = custom.name();
+        let backends = ["postgres", "mysql", "sqlite"]
+            .into_iter()
+            .map(|backend| (backend.to_string(), custom.sql_type(backend)))
+            .filter(|(_, sql_type)| *sql_type != name)
+            .collect();
+        Self { name, backends }
+    }
+}
+
+impl CustomType for SqlType {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn sql_type(&self, backend: &str) -> String {
+        self.backends.get(backend).unwrap_or(&self.name).clone()
+    }
+}
+
+/// A bare string is shorthand for a [`SqlType`] with no backend overrides.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum SqlTypeRepr {
+    Name(String),
+    Full(SqlType),
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_custom<S>(
+    custom: &std::sync::Arc<dyn CustomType>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serde::Serialize::serialize(&SqlType::of(custom.as_ref()), serializer)
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_custom<'de, D>(
+    deserializer: D,
+) -> Result<std::sync::Arc<dyn CustomType>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let sql_type = match <SqlTypeRepr as serde::Deserialize>::deserialize(deserializer)? {
+        SqlTypeRepr::Name(name) => SqlType::new(name),
+        SqlTypeRepr::Full(sql_type) => sql_type,
+    };
+    Ok(std::sync::Arc::new(sql_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct Vector(usize);
+
+    impl CustomType for Vector {
+        fn name(&self) -> String {
+            format!("vector({})", self.0)
+        }
+
+        fn sql_type(&self, backend: &str) -> String {
+            match backend {
+                "postgres" => self.name(),
+                _ => "blob".to_string(),
+            }
+        }
+    }
+
+    #[test]
+    fn sql_type_overrides_per_backend() {
+        let citext = SqlType::new("citext").on("sqlite", "text COLLATE NOCASE");
+        assert_eq!(citext.sql_type("postgres"), "citext");
+        assert_eq!(citext.sql_type("sqlite"), "text COLLATE NOCASE");
+    }
+
+    #[test]
+    fn custom_types_compare_by_name() {
+        let vector: &dyn CustomType = &Vector(3);
+        let same: &dyn CustomType = &SqlType::new("vector(3)");
+        let other: &dyn CustomType = &Vector(4);
+        assert!(vector == same);
+        assert!(vector != other);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialized_as_rendered_sql() {
+        let sql_type = SqlType::of(&Vector(3));
+        assert_eq!(
+            sql_type,
+            SqlType::new("vector(3)")
+                .on("mysql", "blob")
+                .on("sqlite", "blob")
+        );
+    }
+}
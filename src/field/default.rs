@@ -0,0 +1,168 @@
+use crate::backend::Backend;
+
+/// A column default, rendered for each backend when SQL is generated.
+///
+/// Strings convert to [`DefaultValue::Raw`], so `.default("'active'")` keeps
+/// passing SQL through unchanged; use [`DefaultValue::string`] for a string
+/// literal that is quoted for you.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DefaultValue {
+    String(String),
+    Integer(i64),
+    Bool(bool),
+    /// A numeric literal kept as written, e.g. `"9.99"`.
+    Decimal(String),
+    CurrentTimestamp,
+    /// A random UUID generated by the database.
+    GeneratedUuid,
+    /// A SQL expression used verbatim on every backend.
+    Raw(String),
+}
+
+impl DefaultValue {
+    pub fn string(value: impl Into<String>) -> Self {
+        DefaultValue::String(value.into())
+    }
+
+    pub fn decimal(value: impl Into<String>) -> Self {
+        DefaultValue::Decimal(value.into())
+    }
+
+    pub fn raw(sql: impl Into<String>) -> Self {
+        DefaultValue::Raw(sql.into())
+    }
+
+    /// The SQL for this default on `backend`.
+    pub fn to_sql<B: Backend + ?Sized>(&self, backend: &B) -> String {
+        match self {
+            DefaultValue::String(value) => backend.string_literal(value),
+            DefaultValue::Integer(value) => value.to_string(),
+            DefaultValue::Bool(value) => match (backend.name(), value) {
+                ("sqlite" | "mysql", true) => "1".to_string(),
+                ("sqlite" | "mysql", false) => "0".to_string(),
+                (_, true) => "TRUE".to_string(),
+                (_, false) => "FALSE".to_string(),
+            },
+            DefaultValue::Decimal(value) | DefaultValue::Raw(value) => value.clone(),
+            DefaultValue::CurrentTimestamp => "CURRENT_TIMESTAMP".to_string(),
+            DefaultValue::GeneratedUuid => match backend.name() {
+                "mysql" => "(UUID())".to_string(),
+                // SQLite has no UUID function; assemble a version 4 UUID.
+                "sqlite" => "(lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) \
+                             || '-4' || substr(lower(hex(randomblob(2))), 2) || '-' \
+                             || substr('89ab', abs(random()) % 4 + 1, 1) \
+                             || substr(lower(hex(randomblob(2))), 2) || '-' \
+                             || lower(hex(randomblob(6))))"
+                    .to_string(),
+                _ => "gen_random_uuid()".to_string(),
+            },
+        }
+    }
+}
+
+impl From<&str> for DefaultValue {
+    fn from(sql: &str) -> Self {
+        DefaultValue::Raw(sql.to_string())
+    }
+}
+
+impl From<String> for DefaultValue {
+    fn from(sql: String) -> Self {
+        DefaultValue::Raw(sql)
+    }
+}
+
+impl From<i64> for DefaultValue {
+    fn from(value: i64) -> Self {
+        DefaultValue::Integer(value)
+    }
+}
+
+impl From<i32> for DefaultValue {
+    fn from(value: i32) -> Self {
+        DefaultValue::Integer(value.into())
+    }
+}
+
+impl From<bool> for DefaultValue {
+    fn from(value: bool) -> Self {
+        DefaultValue::Bool(value)
+    }
+}
+
+/// Defaults were plain SQL strings before they were typed; those still
+/// deserialize, as [`DefaultValue::Raw`].
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum DefaultValueRepr {
+    Typed(DefaultValue),
+    Raw(String),
+}
+
+#[cfg(feature = "serde")]
+impl From<DefaultValueRepr> for DefaultValue {
+    fn from(repr: DefaultValueRepr) -> Self {
+        match repr {
+            DefaultValueRepr::Typed(value) => value,
+            DefaultValueRepr::Raw(sql) => DefaultValue::Raw(sql),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_default<'de, D>(deserializer: D) -> Result<Option<DefaultValue>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    <Option<DefaultValueRepr> as serde::Deserialize>::deserialize(deserializer)
+        .map(|repr| repr.map(DefaultValue::from))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{MySql, Postgres, Sqlite};
+
+    #[test]
+    fn literals_per_backend() {
+        let status = DefaultValue::string("it's");
+        assert_eq!(status.to_sql(&Postgres), "E'it\\'s'");
+        assert_eq!(status.to_sql(&MySql), "'it\\'s'");
+        assert_eq!(status.to_sql(&Sqlite), "'it''s'");
+
+        assert_eq!(DefaultValue::from(true).to_sql(&Postgres), "TRUE");
+        assert_eq!(DefaultValue::from(true).to_sql(&MySql), "1");
+        assert_eq!(DefaultValue::from(false).to_sql(&Sqlite), "0");
+
+        assert_eq!(DefaultValue::from(-3).to_sql(&Sqlite), "-3");
+        assert_eq!(DefaultValue::decimal("9.99").to_sql(&MySql), "9.99");
+    }
+
+    #[test]
+    fn functions_per_backend() {
+        assert_eq!(
+            DefaultValue::CurrentTimestamp.to_sql(&MySql),
+            "CURRENT_TIMESTAMP"
+        );
+        assert_eq!(
+            DefaultValue::GeneratedUuid.to_sql(&Postgres),
+            "gen_random_uuid()"
+        );
+        assert_eq!(DefaultValue::GeneratedUuid.to_sql(&MySql), "(UUID())");
+        assert!(DefaultValue::GeneratedUuid
+            .to_sql(&Sqlite)
+            .starts_with("(lower(hex(randomblob(4)))"));
+    }
+
+    #[test]
+    fn strings_convert_to_raw_sql() {
+        assert_eq!(
+            DefaultValue::from("'active'"),
+            DefaultValue::Raw("'active'".to_string())
+        );
+        assert_eq!(DefaultValue::raw("now()").to_sql(&Sqlite), "now()");
+    }
+}
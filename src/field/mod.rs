@@ -1,27 +1,63 @@
+mod custom;
+mod default;
 mod types;
 
+#[cfg(feature = "serde")]
+pub(crate) use custom::{deserialize_custom, serialize_custom};
+pub use custom::{CustomType, SqlType};
+
+#[cfg(feature = "serde")]
+pub(crate) use default::deserialize_default;
+pub use default::DefaultValue;
 pub use types::FieldType;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Field {
     pub name: String,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub field_type: FieldType,
+    #[cfg_attr(feature = "serde", serde(default = "default_nullable"))]
     pub nullable: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub primary_key: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub unique: bool,
-    pub default: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "default::deserialize_default"
+        )
+    )]
+    pub default: Option<DefaultValue>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub references: Option<ForeignKey>,
 }
 
+#[cfg(feature = "serde")]
+fn default_nullable() -> bool {
+    true
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ForeignKey {
     pub table: String,
     pub column: String,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub on_delete: ReferentialAction,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub on_update: ReferentialAction,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ReferentialAction {
     #[default]
     NoAction,
@@ -60,7 +96,9 @@ impl Field {
         self
     }
 
-    pub fn default(mut self, value: impl Into<String>) -> Self {
+    /// Set the column default. Strings are used as raw SQL; pass a
+    /// [`DefaultValue`] for a literal rendered per backend.
+    pub fn default(mut self, value: impl Into<DefaultValue>) -> Self {
         self.default = Some(value.into());
         self
     }
@@ -130,7 +168,10 @@ mod tests {
             .not_null()
             .default("'active'");
 
-        assert_eq!(field.default, Some("'active'".to_string()));
+        assert_eq!(field.default, Some(DefaultValue::raw("'active'")));
+
+        let field = Field::new("active", FieldType::Boolean).default(true);
+        assert_eq!(field.default, Some(DefaultValue::Bool(true)));
     }
 
     #[test]
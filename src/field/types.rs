@@ -1,4 +1,10 @@
+use std::sync::Arc;
+
+use super::CustomType;
+
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum FieldType {
     Serial,
     BigSerial,
@@ -17,13 +23,153 @@ pub enum FieldType {
     JsonB,
     Binary,
     Real,
+    #[cfg_attr(feature = "serde", serde(rename = "double_precision"))]
     DoublePrecision,
-    Decimal { precision: u8, scale: u8 },
+    Decimal {
+        precision: u8,
+        scale: u8,
+    },
+    /// One of a fixed set of strings: a named type on PostgreSQL (see
+    /// `CreateEnum`), an inline `ENUM(...)` on MySQL and a text column with a
+    /// `CHECK` constraint on SQLite.
+    Enum {
+        name: String,
+        variants: Vec<String>,
+    },
+    /// An array of `element`; stored as JSON on backends without arrays.
+    Array(Box<FieldType>),
+    Interval,
+    /// A time of day with a UTC offset.
+    TimeTz,
+    Inet,
+    Cidr,
+    MacAddr,
+    /// Fixed-length, blank-padded text.
+    Char(usize),
+    TinyInt,
+    #[cfg_attr(feature = "serde", serde(rename = "unsigned_tinyint"))]
+    UnsignedTinyInt,
+    #[cfg_attr(feature = "serde", serde(rename = "unsigned_smallint"))]
+    UnsignedSmallInt,
+    #[cfg_attr(feature = "serde", serde(rename = "unsigned_integer"))]
+    UnsignedInteger,
+    #[cfg_attr(feature = "serde", serde(rename = "unsigned_bigint"))]
+    UnsignedBigInt,
+    Money,
+    Xml,
+    /// Binary data of exactly this many bytes.
+    #[cfg_attr(feature = "serde", serde(rename = "fixed_binary"))]
+    FixedBinary(usize),
+    /// Binary data of at most this many bytes.
+    VarBinary(usize),
+    /// A type defined outside this crate; see [`CustomType`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "super::serialize_custom",
+            deserialize_with = "super::deserialize_custom"
+        )
+    )]
+    Custom(Arc<dyn CustomType>),
+}
+
+impl FieldType {
+    pub fn enumeration(name: impl Into<String>, variants: &[&str]) -> Self {
+        FieldType::Enum {
+            name: name.into(),
+            variants: variants.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    pub fn array(element: FieldType) -> Self {
+        FieldType::Array(Box::new(element))
+    }
+
+    pub fn custom(custom: impl CustomType + 'static) -> Self {
+        FieldType::Custom(Arc::new(custom))
+    }
+
+    /// The type used instead of this one on backends without a native
+    /// equivalent (see `Backend::supports_field_type`), or `None` when every
+    /// backend has one.
+    pub fn fallback(&self) -> Option<FieldType> {
+        let fallback = match self {
+            FieldType::Array(_) => FieldType::Json,
+            FieldType::Interval | FieldType::Xml => FieldType::Text,
+            FieldType::TimeTz => FieldType::VarChar(32),
+            // Room for an IPv6 address with a prefix length.
+            FieldType::Inet | FieldType::Cidr => FieldType::VarChar(49),
+            FieldType::MacAddr => FieldType::VarChar(17),
+            FieldType::Money => FieldType::Decimal {
+                precision: 19,
+                scale: 4,
+            },
+            // Widen so the whole unsigned range still fits.
+            FieldType::UnsignedTinyInt => FieldType::SmallInt,
+            FieldType::UnsignedSmallInt => FieldType::Integer,
+            FieldType::UnsignedInteger => FieldType::BigInt,
+            FieldType::UnsignedBigInt => FieldType::Decimal {
+                precision: 20,
+                scale: 0,
+            },
+            _ => return None,
+        };
+        Some(fallback)
+    }
+}
+
+/// Backend-neutral type names for display, e.g. in diagrams. Migration
+/// files use the serde representation, not these names.
+impl std::fmt::Display for FieldType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldType::Serial => write!(f, "serial"),
+            FieldType::BigSerial => write!(f, "bigserial"),
+            FieldType::Integer => write!(f, "integer"),
+            FieldType::BigInt => write!(f, "bigint"),
+            FieldType::SmallInt => write!(f, "smallint"),
+            FieldType::Text => write!(f, "text"),
+            FieldType::VarChar(len) => write!(f, "varchar({})", len),
+            FieldType::Boolean => write!(f, "boolean"),
+            FieldType::Timestamp => write!(f, "timestamp"),
+            FieldType::TimestampTz => write!(f, "timestamptz"),
+            FieldType::Date => write!(f, "date"),
+            FieldType::Time => write!(f, "time"),
+            FieldType::Uuid => write!(f, "uuid"),
+            FieldType::Json => write!(f, "json"),
+            FieldType::JsonB => write!(f, "jsonb"),
+            FieldType::Binary => write!(f, "binary"),
+            FieldType::Real => write!(f, "real"),
+            FieldType::DoublePrecision => write!(f, "double_precision"),
+            FieldType::Decimal { precision, scale } => {
+                write!(f, "decimal({},{})", precision, scale)
+            }
+            FieldType::Enum { name, .. } => write!(f, "enum({})", name),
+            FieldType::Array(element) => write!(f, "{}[]", element),
+            FieldType::Interval => write!(f, "interval"),
+            FieldType::TimeTz => write!(f, "timetz"),
+            FieldType::Inet => write!(f, "inet"),
+            FieldType::Cidr => write!(f, "cidr"),
+            FieldType::MacAddr => write!(f, "macaddr"),
+            FieldType::Char(len) => write!(f, "char({})", len),
+            FieldType::TinyInt => write!(f, "tinyint"),
+            FieldType::UnsignedTinyInt => write!(f, "unsigned_tinyint"),
+            FieldType::UnsignedSmallInt => write!(f, "unsigned_smallint"),
+            FieldType::UnsignedInteger => write!(f, "unsigned_integer"),
+            FieldType::UnsignedBigInt => write!(f, "unsigned_bigint"),
+            FieldType::Money => write!(f, "money"),
+            FieldType::Xml => write!(f, "xml"),
+            FieldType::FixedBinary(len) => write!(f, "binary({})", len),
+            FieldType::VarBinary(len) => write!(f, "varbinary({})", len),
+            FieldType::Custom(custom) => write!(f, "{}", custom.name()),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::field::SqlType;
 
     #[test]
     fn field_type_clone_and_eq() {
@@ -45,4 +191,43 @@ mod tests {
             panic!("Expected Decimal variant");
         }
     }
+
+    #[test]
+    fn display_names() {
+        assert_eq!(FieldType::VarChar(64).to_string(), "varchar(64)");
+        assert_eq!(FieldType::DoublePrecision.to_string(), "double_precision");
+        assert_eq!(
+            FieldType::Decimal {
+                precision: 10,
+                scale: 2
+            }
+            .to_string(),
+            "decimal(10,2)"
+        );
+        assert_eq!(
+            FieldType::array(FieldType::VarChar(16)).to_string(),
+            "varchar(16)[]"
+        );
+        assert_eq!(FieldType::FixedBinary(16).to_string(), "binary(16)");
+        assert_eq!(FieldType::UnsignedBigInt.to_string(), "unsigned_bigint");
+        assert_eq!(
+            FieldType::custom(SqlType::new("geometry(Point, 4326)")).to_string(),
+            "geometry(Point, 4326)"
+        );
+    }
+
+    #[test]
+    fn fallbacks() {
+        assert_eq!(
+            FieldType::array(FieldType::Integer).fallback(),
+            Some(FieldType::Json)
+        );
+        assert_eq!(FieldType::Inet.fallback(), Some(FieldType::VarChar(49)));
+        assert_eq!(
+            FieldType::UnsignedInteger.fallback(),
+            Some(FieldType::BigInt)
+        );
+        assert_eq!(FieldType::Char(2).fallback(), None);
+        assert_eq!(FieldType::Integer.fallback(), None);
+    }
 }
@@ -0,0 +1,238 @@
+//! Render the migration dependency graph as Graphviz DOT or Mermaid.
+//!
+//! Edges point from a dependency to the migrations that depend on it, so
+//! the graph reads in execution order. Nodes are labelled with their
+//! applied/pending status and flagged when non-atomic or irreversible.
+
+use crate::migration::{MigrationError, MigrationRegistry};
+use crate::migrator::{state_error, MigrationStateStore};
+
+const APPLIED_FILL: &str = "#c8e6c9";
+const PENDING_FILL: &str = "#ffffff";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+struct Node<'r> {
+    name: &'r str,
+    applied: bool,
+    atomic: bool,
+    reversible: bool,
+    dependencies: Vec<&'r str>,
+}
+
+impl Node<'_> {
+    fn notes(&self) -> Vec<&'static str> {
+        let mut notes = vec![if self.applied { "applied" } else { "pending" }];
+        if !self.atomic {
+            notes.push("non-atomic");
+        }
+        if !self.reversible {
+            notes.push("irreversible");
+        }
+        notes
+    }
+}
+
+/// Render the graph, marking migrations in `applied` as applied.
+pub fn render(
+    registry: &MigrationRegistry,
+    applied: &[String],
+    format: GraphFormat,
+) -> Result<String, MigrationError> {
+    let mut nodes = Vec::new();
+    for name in registry.resolve_order()? {
+        let migration = registry
+            .get(name)
+            .ok_or_else(|| MigrationError::NotFound(name.to_string()))?;
+        nodes.push(Node {
+            name,
+            applied: applied.iter().any(|a| a == name),
+            atomic: migration.is_atomic(),
+            reversible: migration.is_reversible(),
+            dependencies: registry.resolved_dependencies(name)?,
+        });
+    }
+
+    Ok(match format {
+        GraphFormat::Dot => render_dot(&nodes),
+        GraphFormat::Mermaid => render_mermaid(&nodes),
+    })
+}
+
+/// Render the graph with applied status read from a state store.
+pub fn render_with_state<S: MigrationStateStore>(
+    registry: &MigrationRegistry,
+    state: &mut S,
+    format: GraphFormat,
+) -> Result<String, MigrationError> {
+    let applied = state.applied_migrations().map_err(state_error)?;
+    render(registry, &applied, format)
+}
+
+fn render_dot(nodes: &[Node]) -> String {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    let quote = |s: &str| format!("\"{}\"", escape(s));
+
+    let mut out = String::from(
+        "digraph migrations {\n    rankdir=TB;\n    node [shape=box, style=filled];\n",
+    );
+    for node in nodes {
+        out.push_str(&format!(
+            "    {} [label=\"{}\\n{}\", fillcolor=\"{}\"{}];\n",
+            quote(node.name),
+            escape(node.name),
+            node.notes().join(", "),
+            if node.applied {
+                APPLIED_FILL
+            } else {
+                PENDING_FILL
+            },
+            if node.reversible {
+                ""
+            } else {
+                ", peripheries=2"
+            },
+        ));
+    }
+    for node in nodes {
+        for dep in &node.dependencies {
+            out.push_str(&format!("    {} -> {};\n", quote(dep), quote(node.name)));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn render_mermaid(nodes: &[Node]) -> String {
+    // Mermaid ids can't hold arbitrary names, so nodes are numbered in order.
+    let id = |name: &str| {
+        nodes
+            .iter()
+            .position(|node| node.name == name)
+            .map(|i| format!("m{}", i))
+            .unwrap_or_default()
+    };
+
+    let mut out = String::from("flowchart TD\n");
+    for (i, node) in nodes.iter().enumerate() {
+        out.push_str(&format!(
+            "    m{}[\"{}<br/>{}\"]\n",
+            i,
+            node.name.replace('"', "#quot;"),
+            node.notes().join(", ")
+        ));
+    }
+    for node in nodes {
+        for dep in &node.dependencies {
+            out.push_str(&format!("    {} --> {}\n", id(dep), id(node.name)));
+        }
+    }
+
+    out.push_str(&format!(
+        "    classDef applied fill:{}\n    classDef pending fill:{}\n",
+        APPLIED_FILL, PENDING_FILL
+    ));
+    for (class, applied) in [("applied", true), ("pending", false)] {
+        let ids: Vec<String> = (0..nodes.len())
+            .filter(|&i| nodes[i].applied == applied)
+            .map(|i| format!("m{}", i))
+            .collect();
+        if !ids.is_empty() {
+            out.push_str(&format!("    class {} {}\n", ids.join(","), class));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migration::Migration;
+    use crate::migrator::InMemoryState;
+    use crate::operation::RunSql;
+
+    fn setup_registry() -> MigrationRegistry {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_users"));
+        registry.register(Migration::new("0002_posts").depends_on(&["0001_users"]));
+        registry.register(
+            Migration::new("0003_index")
+                .depends_on(&["0001_users"])
+                .atomic(false),
+        );
+        registry.register(
+            Migration::new("0004_merge")
+                .depends_on(&["0002_posts", "0003_index"])
+                .operation(RunSql::new("UPDATE posts SET id = id")),
+        );
+        registry
+    }
+
+    #[test]
+    fn dot_output() {
+        let applied = vec!["0001_users".to_string()];
+        let dot = render(&setup_registry(), &applied, GraphFormat::Dot).unwrap();
+
+        assert!(dot.starts_with("digraph migrations {\n"));
+        assert!(dot.contains(
+            "    \"0001_users\" [label=\"0001_users\\napplied\", fillcolor=\"#c8e6c9\"];\n"
+        ));
+        assert!(dot.contains("\"0003_index\" [label=\"0003_index\\npending, non-atomic\""));
+        assert!(dot.contains(
+            "\"0004_merge\" [label=\"0004_merge\\npending, irreversible\", fillcolor=\"#ffffff\", peripheries=2];"
+        ));
+        assert!(dot.contains("    \"0001_users\" -> \"0002_posts\";\n"));
+        assert!(dot.contains("    \"0003_index\" -> \"0004_merge\";\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn mermaid_output() {
+        let mut state =
+            InMemoryState::with_applied(vec!["0001_users".to_string(), "0002_posts".to_string()]);
+        let mermaid =
+            render_with_state(&setup_registry(), &mut state, GraphFormat::Mermaid).unwrap();
+
+        assert_eq!(
+            mermaid,
+            "flowchart TD\n\
+             \x20   m0[\"0001_users<br/>applied\"]\n\
+             \x20   m1[\"0002_posts<br/>applied\"]\n\
+             \x20   m2[\"0003_index<br/>pending, non-atomic\"]\n\
+             \x20   m3[\"0004_merge<br/>pending, irreversible\"]\n\
+             \x20   m0 --> m1\n\
+             \x20   m0 --> m2\n\
+             \x20   m1 --> m3\n\
+             \x20   m2 --> m3\n\
+             \x20   classDef applied fill:#c8e6c9\n\
+             \x20   classDef pending fill:#ffffff\n\
+             \x20   class m0,m1 applied\n\
+             \x20   class m2,m3 pending\n"
+        );
+    }
+
+    #[test]
+    fn run_before_edges_are_drawn() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_users"));
+        registry.register(Migration::new("audit").run_before(&["0001_users"]));
+
+        let dot = render(&registry, &[], GraphFormat::Dot).unwrap();
+        assert!(dot.contains("\"audit\" -> \"0001_users\";"));
+    }
+
+    #[test]
+    fn quotes_in_names_are_escaped() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("say \"hi\""));
+
+        let dot = render(&registry, &[], GraphFormat::Dot).unwrap();
+        assert!(dot.contains("\"say \\\"hi\\\"\" [label="));
+        let mermaid = render(&registry, &[], GraphFormat::Mermaid).unwrap();
+        assert!(mermaid.contains("m0[\"say #quot;hi#quot;<br/>pending\"]"));
+    }
+}
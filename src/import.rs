@@ -0,0 +1,419 @@
+//! Import applied migrations from another tool's tracking table.
+//!
+//! When a service switches to cetane, the previous tool's tracking table
+//! already records what ran. The importer reads those rows through a query
+//! closure (like the executor closures used by [`Migrator`](crate::migrator::Migrator)),
+//! matches each row to a registered cetane migration and records it with
+//! [`MigrationStateStore::mark_applied`].
+
+use std::collections::HashMap;
+
+use crate::backend::Backend;
+use crate::migration::MigrationRegistry;
+use crate::migrator::MigrationStateStore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalTool {
+    /// Diesel's `__diesel_schema_migrations` (versions only, no names).
+    Diesel,
+    /// refinery's `refinery_schema_history`.
+    Refinery,
+    /// sqlx's `_sqlx_migrations` (only successful rows are imported).
+    Sqlx,
+}
+
+impl ExternalTool {
+    pub fn default_table(&self) -> &'static str {
+        match self {
+            ExternalTool::Diesel => "__diesel_schema_migrations",
+            ExternalTool::Refinery => "refinery_schema_history",
+            ExternalTool::Sqlx => "_sqlx_migrations",
+        }
+    }
+
+    /// Query returning `(version, name)` text columns from the default table.
+    pub fn query_sql(&self, backend: &dyn Backend) -> String {
+        self.query_sql_for_table(backend, self.default_table())
+    }
+
+    /// Query returning `(version, name)` text columns, in applied order.
+    pub fn query_sql_for_table(&self, backend: &dyn Backend, table: &str) -> String {
+        let text = if backend.name() == "mysql" {
+            "CHAR"
+        } else {
+            "TEXT"
+        };
+        let table = backend.quote_identifier(table);
+
+        match self {
+            ExternalTool::Diesel => format!(
+                "SELECT CAST(version AS {text}), CAST(NULL AS {text}) FROM {table} ORDER BY version"
+            ),
+            ExternalTool::Refinery => format!(
+                "SELECT CAST(version AS {text}), name FROM {table} ORDER BY version"
+            ),
+            ExternalTool::Sqlx => format!(
+                "SELECT CAST(version AS {text}), description FROM {table} WHERE success ORDER BY version"
+            ),
+        }
+    }
+}
+
+/// A row from another tool's tracking table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExternalMigration {
+    pub version: String,
+    pub name: Option<String>,
+}
+
+impl ExternalMigration {
+    pub fn new(version: impl Into<String>, name: Option<&str>) -> Self {
+        Self {
+            version: version.into(),
+            name: name.map(str::to_string),
+        }
+    }
+}
+
+type MatchFn = Box<dyn Fn(&ExternalMigration) -> Option<String> + Send + Sync>;
+
+/// How external rows are matched to cetane migration names.
+pub enum MatchRule {
+    /// Explicit external version to cetane name.
+    Mapping(HashMap<String, String>),
+    /// Match the migration whose leading digits equal the external version,
+    /// ignoring leading zeros (`V3` / `3` matches `0003_add_posts`).
+    VersionPrefix,
+    /// Any other rule. Returning `None` leaves the row unmatched.
+    Custom(MatchFn),
+}
+
+impl MatchRule {
+    fn resolve(
+        &self,
+        row: &ExternalMigration,
+        registry: &MigrationRegistry,
+    ) -> Result<Option<String>, ImportError> {
+        let name = match self {
+            MatchRule::Mapping(map) => map.get(&row.version).cloned(),
+            MatchRule::VersionPrefix => {
+                let version = leading_number(&row.version);
+                let candidates: Vec<String> = registry
+                    .all()
+                    .filter(|m| version.is_some() && leading_number(&m.name) == version)
+                    .map(|m| m.qualified_name().to_string())
+                    .collect();
+                if candidates.len() > 1 {
+                    return Err(ImportError::Ambiguous {
+                        version: row.version.clone(),
+                        candidates,
+                    });
+                }
+                candidates.into_iter().next()
+            }
+            MatchRule::Custom(rule) => rule(row),
+        };
+
+        match name {
+            Some(name) if registry.get(&name).is_none() => Err(ImportError::UnknownMigration {
+                version: row.version.clone(),
+                name,
+            }),
+            name => Ok(name),
+        }
+    }
+}
+
+fn leading_number(s: &str) -> Option<u128> {
+    let digits: String = s
+        .trim_start_matches(['V', 'v'])
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    digits.parse().ok()
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ImportPlan {
+    pub matched: Vec<(ExternalMigration, String)>,
+    pub unmatched: Vec<ExternalMigration>,
+}
+
+/// Match external rows to registered migrations without writing anything.
+pub fn plan_import(
+    rows: Vec<ExternalMigration>,
+    registry: &MigrationRegistry,
+    rule: &MatchRule,
+) -> Result<ImportPlan, ImportError> {
+    let mut plan = ImportPlan::default();
+
+    for row in rows {
+        match rule.resolve(&row, registry)? {
+            Some(name) => plan.matched.push((row, name)),
+            None => plan.unmatched.push(row),
+        }
+    }
+
+    Ok(plan)
+}
+
+impl ImportPlan {
+    /// Mark every matched migration as applied, skipping ones the store
+    /// already knows about. Returns the newly marked names.
+    pub fn apply<S: MigrationStateStore>(&self, state: &mut S) -> Result<Vec<String>, ImportError> {
+        let already = state.applied_migrations().map_err(ImportError::State)?;
+        let mut imported = Vec::new();
+
+        for (_, name) in &self.matched {
+            if already.contains(name) || imported.contains(name) {
+                continue;
+            }
+            state.mark_applied(name).map_err(ImportError::State)?;
+            imported.push(name.clone());
+        }
+
+        Ok(imported)
+    }
+}
+
+/// Read another tool's tracking table and record its rows as applied.
+///
+/// `query` runs the given SQL and returns `(version, name)` rows. Fails
+/// without writing anything if any row cannot be matched.
+pub fn import<S, Q>(
+    tool: ExternalTool,
+    backend: &dyn Backend,
+    registry: &MigrationRegistry,
+    rule: &MatchRule,
+    mut query: Q,
+    state: &mut S,
+) -> Result<Vec<String>, ImportError>
+where
+    S: MigrationStateStore,
+    Q: FnMut(&str) -> Result<Vec<ExternalMigration>, String>,
+{
+    let rows = query(&tool.query_sql(backend)).map_err(ImportError::Query)?;
+    let plan = plan_import(rows, registry, rule)?;
+
+    if !plan.unmatched.is_empty() {
+        return Err(ImportError::Unmatched(plan.unmatched));
+    }
+
+    plan.apply(state)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportError {
+    Query(String),
+    State(String),
+    Unmatched(Vec<ExternalMigration>),
+    Ambiguous {
+        version: String,
+        candidates: Vec<String>,
+    },
+    UnknownMigration {
+        version: String,
+        name: String,
+    },
+}
+
+impl std::fmt::Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Query(error) => write!(f, "Failed to read tracking table: {}", error),
+            ImportError::State(error) => write!(f, "Failed to record migration: {}", error),
+            ImportError::Unmatched(rows) => {
+                let versions: Vec<&str> = rows.iter().map(|r| r.version.as_str()).collect();
+                write!(f, "No migration matches versions: {}", versions.join(", "))
+            }
+            ImportError::Ambiguous {
+                version,
+                candidates,
+            } => write!(
+                f,
+                "Version {} matches several migrations: {}",
+                version,
+                candidates.join(", ")
+            ),
+            ImportError::UnknownMigration { version, name } => {
+                write!(f, "Version {} maps to unknown migration: {}", version, name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{MySql, Postgres};
+    use crate::migration::Migration;
+    use crate::migrator::InMemoryState;
+
+    fn setup_registry() -> MigrationRegistry {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_create_users"));
+        registry.register(Migration::new("0002_add_posts").depends_on(&["0001_create_users"]));
+        registry.register(Migration::new("0003_add_tags").depends_on(&["0002_add_posts"]));
+        registry
+    }
+
+    fn rows(versions: &[&str]) -> Vec<ExternalMigration> {
+        versions
+            .iter()
+            .map(|v| ExternalMigration::new(*v, None))
+            .collect()
+    }
+
+    #[test]
+    fn tool_queries() {
+        assert_eq!(
+            ExternalTool::Diesel.query_sql(&Postgres),
+            "SELECT CAST(version AS TEXT), CAST(NULL AS TEXT) FROM \"__diesel_schema_migrations\" ORDER BY version"
+        );
+        assert_eq!(
+            ExternalTool::Refinery.query_sql(&MySql),
+            "SELECT CAST(version AS CHAR), name FROM `refinery_schema_history` ORDER BY version"
+        );
+        assert_eq!(
+            ExternalTool::Sqlx.query_sql_for_table(&Postgres, "custom"),
+            "SELECT CAST(version AS TEXT), description FROM \"custom\" WHERE success ORDER BY version"
+        );
+    }
+
+    #[test]
+    fn version_prefix_ignores_leading_zeros() {
+        let registry = setup_registry();
+        let plan = plan_import(
+            rows(&["1", "V2", "7"]),
+            &registry,
+            &MatchRule::VersionPrefix,
+        )
+        .unwrap();
+
+        let names: Vec<_> = plan.matched.iter().map(|(_, n)| n.as_str()).collect();
+        assert_eq!(names, vec!["0001_create_users", "0002_add_posts"]);
+        assert_eq!(plan.unmatched, rows(&["7"]));
+    }
+
+    #[test]
+    fn version_prefix_reports_ambiguity() {
+        let mut registry = setup_registry();
+        registry.register(Migration::new("0002_duplicate"));
+
+        let result = plan_import(rows(&["2"]), &registry, &MatchRule::VersionPrefix);
+        assert!(matches!(result, Err(ImportError::Ambiguous { .. })));
+    }
+
+    #[test]
+    fn mapping_rule() {
+        let registry = setup_registry();
+        let rule = MatchRule::Mapping(HashMap::from([(
+            "20230101120000".to_string(),
+            "0001_create_users".to_string(),
+        )]));
+
+        let plan = plan_import(rows(&["20230101120000"]), &registry, &rule).unwrap();
+        assert_eq!(plan.matched[0].1, "0001_create_users");
+    }
+
+    #[test]
+    fn mapping_to_unknown_migration_fails() {
+        let registry = setup_registry();
+        let rule = MatchRule::Mapping(HashMap::from([("1".to_string(), "0009_nope".to_string())]));
+
+        let result = plan_import(rows(&["1"]), &registry, &rule);
+        assert!(matches!(result, Err(ImportError::UnknownMigration { .. })));
+    }
+
+    #[test]
+    fn custom_rule_matches_on_name() {
+        let registry = setup_registry();
+        let rule = MatchRule::Custom(Box::new(|row| {
+            row.name
+                .as_ref()
+                .map(|name| format!("{:0>4}_{}", row.version, name))
+        }));
+
+        let plan = plan_import(
+            vec![ExternalMigration::new("3", Some("add_tags"))],
+            &registry,
+            &rule,
+        )
+        .unwrap();
+        assert_eq!(plan.matched[0].1, "0003_add_tags");
+    }
+
+    #[test]
+    fn apply_skips_already_applied() {
+        let registry = setup_registry();
+        let plan = plan_import(rows(&["1", "2"]), &registry, &MatchRule::VersionPrefix).unwrap();
+        let mut state = InMemoryState::with_applied(vec!["0001_create_users".to_string()]);
+
+        let imported = plan.apply(&mut state).unwrap();
+
+        assert_eq!(imported, vec!["0002_add_posts"]);
+        assert_eq!(
+            state.applied_migrations().unwrap(),
+            vec!["0001_create_users", "0002_add_posts"]
+        );
+    }
+
+    #[test]
+    fn import_fails_without_writing_when_rows_unmatched() {
+        let registry = setup_registry();
+        let mut state = InMemoryState::new();
+
+        let result = import(
+            ExternalTool::Refinery,
+            &Postgres,
+            &registry,
+            &MatchRule::VersionPrefix,
+            |_sql| Ok(rows(&["1", "42"])),
+            &mut state,
+        );
+
+        assert_eq!(result, Err(ImportError::Unmatched(rows(&["42"]))));
+        assert!(state.applied_migrations().unwrap().is_empty());
+    }
+
+    #[test]
+    fn import_passes_tool_query() {
+        let registry = setup_registry();
+        let mut state = InMemoryState::new();
+        let mut queries = Vec::new();
+
+        let imported = import(
+            ExternalTool::Sqlx,
+            &Postgres,
+            &registry,
+            &MatchRule::VersionPrefix,
+            |sql| {
+                queries.push(sql.to_string());
+                Ok(rows(&["1", "2", "3"]))
+            },
+            &mut state,
+        )
+        .unwrap();
+
+        assert_eq!(imported.len(), 3);
+        assert!(queries[0].contains("_sqlx_migrations"));
+    }
+
+    #[test]
+    fn import_error_display() {
+        assert_eq!(
+            ImportError::Unmatched(rows(&["4", "5"])).to_string(),
+            "No migration matches versions: 4, 5"
+        );
+        assert_eq!(
+            ImportError::UnknownMigration {
+                version: "1".to_string(),
+                name: "x".to_string()
+            }
+            .to_string(),
+            "Version 1 maps to unknown migration: x"
+        );
+    }
+}
@@ -0,0 +1,383 @@
+//! Dependency inference from operation contents.
+//!
+//! A migration that adds a foreign key to `users`, or alters or drops it,
+//! needs the migration that created `users` to run first. These edges are
+//! derived from [`Operation::touches`](crate::operation::Operation::touches)
+//! and compared against what the registry declares.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::migration::{MigrationError, MigrationRegistry, APP_SEPARATOR};
+use crate::operation::Touch;
+
+/// An edge implied by a migration's operations that the registry doesn't
+/// have, directly or transitively.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingDependency {
+    pub migration: String,
+    /// The migration that creates `table`.
+    pub dependency: String,
+    pub table: String,
+}
+
+/// Report migrations whose dependencies don't guarantee that the tables
+/// they use are created first.
+pub fn missing_dependencies(
+    registry: &MigrationRegistry,
+) -> Result<Vec<MissingDependency>, MigrationError> {
+    let creators = table_creators(registry);
+    let ancestry = Ancestry::new(registry)?;
+    let mut missing = Vec::new();
+
+    for name in registry.resolve_order()? {
+        missing.extend(missing_for(registry, &creators, &ancestry, name)?);
+    }
+
+    Ok(missing)
+}
+
+/// Add every missing dependency to the registry and return what was added.
+///
+/// A global creator can't be named from an app that has a migration with
+/// the same name, since bare names resolve within the app first. Such
+/// edges are left out and still reported by [`missing_dependencies`].
+pub fn infer_dependencies(
+    registry: &mut MigrationRegistry,
+) -> Result<Vec<MissingDependency>, MigrationError> {
+    let creators = table_creators(registry);
+    let mut ancestry = Ancestry::new(registry)?;
+    let names: Vec<String> = registry
+        .resolve_order()?
+        .into_iter()
+        .map(str::to_string)
+        .collect();
+    let mut added = Vec::new();
+
+    for name in names {
+        let mut missing = missing_for(registry, &creators, &ancestry, &name)?;
+        missing.retain(|edge| !shadowed(registry, &name, &edge.dependency));
+        for edge in &missing {
+            registry.add_dependency(&name, edge.dependency.clone())?;
+            ancestry.add_dependency(&name, &edge.dependency);
+        }
+        added.extend(missing);
+    }
+
+    Ok(added)
+}
+
+/// Whether `dependency`, a global migration, would resolve to a same-named
+/// migration of `name`'s app instead.
+fn shadowed(registry: &MigrationRegistry, name: &str, dependency: &str) -> bool {
+    let Some(app) = registry.get(name).and_then(|m| m.app.as_deref()) else {
+        return false;
+    };
+    registry.get(dependency).is_some_and(|m| m.app.is_none())
+        && registry
+            .get(&format!("{}{}{}", app, APP_SEPARATOR, dependency))
+            .is_some()
+}
+
+/// Every migration's transitive dependencies, computed once in dependency
+/// order.
+struct Ancestry {
+    ancestors: HashMap<String, HashSet<String>>,
+}
+
+impl Ancestry {
+    fn new(registry: &MigrationRegistry) -> Result<Self, MigrationError> {
+        let mut ancestors: HashMap<String, HashSet<String>> = HashMap::new();
+        for name in registry.resolve_order()? {
+            let mut own = HashSet::new();
+            for dep in registry.resolved_dependencies(name)? {
+                own.extend(ancestors[dep].iter().cloned());
+                own.insert(dep.to_string());
+            }
+            ancestors.insert(name.to_string(), own);
+        }
+        Ok(Self { ancestors })
+    }
+
+    /// Whether `earlier` is a transitive dependency of `later`.
+    fn runs_before(&self, earlier: &str, later: &str) -> bool {
+        self.ancestors
+            .get(later)
+            .is_some_and(|ancestors| ancestors.contains(earlier))
+    }
+
+    /// Record a new edge: `name`, and everything depending on it, now also
+    /// depend on `dependency` and its ancestors.
+    fn add_dependency(&mut self, name: &str, dependency: &str) {
+        let mut added = self.ancestors.get(dependency).cloned().unwrap_or_default();
+        added.insert(dependency.to_string());
+        for (migration, ancestors) in &mut self.ancestors {
+            if migration == name || ancestors.contains(name) {
+                ancestors.extend(added.iter().cloned());
+            }
+        }
+    }
+}
+
+/// Migrations creating each table (by `CreateTable` or `RenameTable`), in
+/// registration order.
+fn table_creators(registry: &MigrationRegistry) -> HashMap<String, Vec<String>> {
+    let mut creators: HashMap<String, Vec<String>> = HashMap::new();
+    for migration in registry.all() {
+        for touch in migration.touches() {
+            if let Touch::CreateTable(table) = touch {
+                creators
+                    .entry(table)
+                    .or_default()
+                    .push(migration.qualified_name().into_owned());
+            }
+        }
+    }
+    creators
+}
+
+fn missing_for(
+    registry: &MigrationRegistry,
+    creators: &HashMap<String, Vec<String>>,
+    ancestry: &Ancestry,
+    name: &str,
+) -> Result<Vec<MissingDependency>, MigrationError> {
+    let Some(migration) = registry.get(name) else {
+        return Err(MigrationError::NotFound(name.to_string()));
+    };
+    let touches = migration.touches();
+
+    let mut missing: Vec<MissingDependency> = Vec::new();
+    for touch in &touches {
+        let table = match touch {
+            Touch::CreateTable(_) => continue,
+            Touch::DropTable(table) => table,
+            Touch::Alter { table, .. } | Touch::Reference { table, .. } => table,
+        };
+        if touches.contains(&Touch::CreateTable(table.clone())) {
+            continue;
+        }
+
+        // Creators that run after this migration can't be what it relies on.
+        let candidates: Vec<&String> = creators
+            .get(table)
+            .into_iter()
+            .flatten()
+            .filter(|creator| *creator != name && !ancestry.runs_before(name, creator))
+            .collect();
+        if candidates
+            .iter()
+            .any(|creator| ancestry.runs_before(creator, name))
+        {
+            continue;
+        }
+        let Some(dependency) = candidates.last() else {
+            continue;
+        };
+
+        if !missing.iter().any(|m| &m.dependency == *dependency) {
+            missing.push(MissingDependency {
+                migration: name.to_string(),
+                dependency: dependency.to_string(),
+                table: table.clone(),
+            });
+        }
+    }
+
+    Ok(missing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Field, FieldType};
+    use crate::migration::Migration;
+    use crate::operation::{AddConstraint, AddField, Constraint, CreateTable, RunSql};
+
+    fn users() -> CreateTable {
+        CreateTable::new("users").add_field(Field::new("id", FieldType::Serial).primary_key())
+    }
+
+    fn posts() -> CreateTable {
+        CreateTable::new("posts")
+            .add_field(Field::new("id", FieldType::Serial).primary_key())
+            .add_field(Field::new("author_id", FieldType::Integer).references("users", "id"))
+    }
+
+    #[test]
+    fn declared_dependencies_are_enough() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_users").operation(users()));
+        registry.register(Migration::new("0002_orgs").depends_on(&["0001_users"]));
+        registry.register(
+            Migration::new("0003_posts")
+                .depends_on(&["0002_orgs"])
+                .operation(posts()),
+        );
+
+        assert_eq!(missing_dependencies(&registry).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn foreign_key_field_requires_creator() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_users").operation(users()));
+        registry.register(Migration::new("0002_posts").operation(posts()));
+
+        assert_eq!(
+            missing_dependencies(&registry).unwrap(),
+            vec![MissingDependency {
+                migration: "0002_posts".to_string(),
+                dependency: "0001_users".to_string(),
+                table: "users".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn constraint_and_alterations_require_creators() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_users").operation(users()));
+        registry.register(
+            Migration::new("0002_orgs")
+                .operation(CreateTable::new("orgs").field("id", FieldType::Serial)),
+        );
+        registry.register(
+            Migration::new("0003_membership")
+                .operation(AddField::new(
+                    "users",
+                    Field::new("org_id", FieldType::Integer),
+                ))
+                .operation(AddConstraint::new(
+                    "users",
+                    Constraint::foreign_key(
+                        "fk_users_org",
+                        vec!["org_id".to_string()],
+                        "orgs",
+                        vec!["id".to_string()],
+                    ),
+                )),
+        );
+
+        let missing = missing_dependencies(&registry).unwrap();
+        let deps: Vec<&str> = missing.iter().map(|m| m.dependency.as_str()).collect();
+        assert_eq!(deps, vec!["0001_users", "0002_orgs"]);
+    }
+
+    #[test]
+    fn tables_created_elsewhere_are_ignored() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_raw")
+                .operation(RunSql::new("CREATE TABLE users (id INTEGER PRIMARY KEY)")),
+        );
+        registry.register(Migration::new("0002_posts").operation(posts()));
+        registry.register(
+            Migration::new("0003_self").operation(
+                CreateTable::new("nodes")
+                    .add_field(Field::new("id", FieldType::Serial).primary_key())
+                    .add_field(
+                        Field::new("parent_id", FieldType::Integer).references("nodes", "id"),
+                    ),
+            ),
+        );
+
+        assert_eq!(missing_dependencies(&registry).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn later_creators_are_not_suggested() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_posts").operation(posts()));
+        registry.register(
+            Migration::new("0002_users")
+                .depends_on(&["0001_posts"])
+                .operation(users()),
+        );
+
+        assert_eq!(missing_dependencies(&registry).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn infer_adds_edges() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0002_posts").operation(posts()));
+        registry.register(Migration::new("0001_users").operation(users()));
+
+        let added = infer_dependencies(&mut registry).unwrap();
+        assert_eq!(added.len(), 1);
+        assert_eq!(
+            registry.get("0002_posts").unwrap().dependencies,
+            vec!["0001_users"]
+        );
+        assert_eq!(
+            registry.resolve_order().unwrap(),
+            vec!["0001_users", "0002_posts"]
+        );
+        assert_eq!(missing_dependencies(&registry).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn infer_reuses_edges_it_added() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_users").operation(users()));
+        registry.register(Migration::new("0002_posts").operation(posts()));
+        registry.register(
+            Migration::new("0003_editors")
+                .depends_on(&["0002_posts"])
+                .operation(AddField::new(
+                    "posts",
+                    Field::new("editor_id", FieldType::Integer).references("users", "id"),
+                )),
+        );
+
+        let added = infer_dependencies(&mut registry).unwrap();
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].migration, "0002_posts");
+        assert_eq!(
+            registry.get("0003_editors").unwrap().dependencies,
+            vec!["0002_posts"]
+        );
+    }
+
+    #[test]
+    fn infer_across_apps() {
+        let mut registry = MigrationRegistry::new();
+        registry.register_app("auth", [Migration::new("0001_initial").operation(users())]);
+        registry.register_app("blog", [Migration::new("0001_initial").operation(posts())]);
+
+        infer_dependencies(&mut registry).unwrap();
+        assert_eq!(
+            registry.resolved_dependencies("blog.0001_initial").unwrap(),
+            vec!["auth.0001_initial"]
+        );
+    }
+
+    #[test]
+    fn infer_refuses_shadowed_global_creators() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_initial").operation(users()));
+        registry.register_app(
+            "blog",
+            [
+                Migration::new("0001_initial"),
+                Migration::new("0002_posts")
+                    .depends_on(&["0001_initial"])
+                    .operation(posts()),
+            ],
+        );
+
+        assert!(infer_dependencies(&mut registry).unwrap().is_empty());
+        assert_eq!(
+            registry.resolved_dependencies("blog.0002_posts").unwrap(),
+            vec!["blog.0001_initial"]
+        );
+        assert_eq!(
+            missing_dependencies(&registry).unwrap(),
+            vec![MissingDependency {
+                migration: "blog.0002_posts".to_string(),
+                dependency: "0001_initial".to_string(),
+                table: "users".to_string(),
+            }]
+        );
+    }
+}
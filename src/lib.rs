@@ -1,21 +1,67 @@
 pub mod backend;
+#[cfg(feature = "serde")]
+pub mod declarative;
+pub mod erd;
+pub mod export;
 pub mod field;
+pub mod graph;
+pub mod import;
+pub mod inference;
+pub mod merge;
 pub mod migration;
 pub mod migrator;
 pub mod operation;
+pub mod schema;
 pub mod state;
 
+/// Embed a directory of raw SQL migrations, evaluating to a `Vec<Migration>`.
+///
+/// The path is relative to the crate's `Cargo.toml`. Each subdirectory
+/// becomes one migration named after the directory, whose operation is a
+/// `RunSql` built from:
+///
+/// - `up.sql` (required) and `down.sql` (optional, makes it reversible)
+/// - `up.<backend>.sql` / `down.<backend>.sql` to override the SQL for one
+///   backend, e.g. `up.postgres.sql`. The backend must be `mysql`,
+///   `postgres` or `sqlite`; other suffixes are a compile error.
+///
+/// Directories are ordered by name and each migration depends on the one
+/// before it. A leading `-- depends_on: 0001_a, 0002_b` comment in `up.sql`
+/// replaces the inferred dependency (leave the list empty for none).
+///
+/// ```ignore
+/// registry.register_all(cetane::embed_sql_migrations!("migrations"));
+/// ```
+///
+/// Edits to embedded files trigger a rebuild, but new migration directories
+/// don't. Have a build script watch the directory so cargo notices them:
+///
+/// ```ignore
+/// // build.rs
+/// fn main() {
+///     println!("cargo:rerun-if-changed=migrations");
+/// }
+/// ```
+#[cfg(feature = "macros")]
+pub use cetane_macros::embed_sql_migrations;
+
 pub mod prelude {
     pub use crate::backend::{Backend, FieldChanges, MySql, Postgres, Sqlite};
-    pub use crate::field::{Field, FieldType, ForeignKey, ReferentialAction};
+    pub use crate::field::{
+        CustomType, DefaultValue, Field, FieldType, ForeignKey, ReferentialAction, SqlType,
+    };
     pub use crate::migration::{Migration, MigrationError, MigrationRegistry};
-    pub use crate::migrator::{InMemoryState, MigrationStateStore, Migrator};
+    pub use crate::migrator::{InMemoryState, MigrationStateStore, Migrator, StoredBackward};
     pub use crate::operation::{
-        AddConstraint, AddField, AddIndex, AlterField, Constraint, CreateTable, DropTable, Index,
-        IndexOrder, Operation, RemoveConstraint, RemoveField, RemoveIndex, RenameField,
-        RenameTable, RunSql,
+        AddConstraint, AddEnumValue, AddField, AddIndex, AlterField, Backfill, Constraint,
+        CreateEnum, CreateTable, DropEnum, DropTable, Index, IndexOrder, InsertRows, Operation,
+        RemoveConstraint, RemoveField, RemoveIndex, RenameEnumValue, RenameField, RenameTable,
+        RunCode, RunContext, RunSql, Statement, Value,
     };
 
+    #[cfg(feature = "serde")]
+    pub use crate::operation::{OperationRegistry, SerializableOperation, SerializedOperation};
+
     #[cfg(feature = "sqlite")]
     pub use crate::state::SqliteMigrationState;
 
@@ -0,0 +1,316 @@
+//! Merging divergent migration histories.
+//!
+//! When two branches each add a migration on top of the same parent, the
+//! registry ends up with two leaves. [`plan_merge`] finds them, lists the
+//! tables and columns changed on more than one branch, and builds an empty
+//! migration that depends on every leaf.
+
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+use crate::migration::{Migration, MigrationError, MigrationRegistry, APP_SEPARATOR};
+use crate::operation::Touch;
+
+/// A table changed by migrations on two different branches, and the
+/// column both change when the clash is over one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    pub table: String,
+    /// The column both branches change. `None` for table-level conflicts:
+    /// one branch creates or drops the table, or both change it without
+    /// naming a column.
+    pub column: Option<String>,
+    pub first: String,
+    pub second: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergePlan {
+    pub app: Option<String>,
+    pub leaves: Vec<String>,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergePlan {
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+
+    /// A name numbered after the highest leaf, e.g. `0004_merge` after
+    /// `0003_add_tags`, or just `merge` when leaves aren't numbered.
+    pub fn default_name(&self) -> String {
+        let numbers = self.leaves.iter().filter_map(|leaf| {
+            let name = leaf.rsplit(APP_SEPARATOR).next().unwrap_or(leaf);
+            let digits: String = name.chars().take_while(char::is_ascii_digit).collect();
+            Some((digits.parse::<u64>().ok()?, digits.len()))
+        });
+
+        match numbers.max() {
+            Some((number, width)) => format!("{:0width$}_merge", number + 1, width = width),
+            None => "merge".to_string(),
+        }
+    }
+
+    /// An empty migration depending on every leaf, placed in the plan's app.
+    pub fn migration(&self, name: impl Into<Cow<'static, str>>) -> Migration {
+        let migration = Migration::new(name).dependencies(self.leaves.clone());
+        match self.app {
+            Some(ref app) => migration.in_app(app.clone()),
+            None => migration,
+        }
+    }
+}
+
+/// Look for divergent leaves in `app` (or among migrations outside any app
+/// for `None`). Returns `None` when the history is linear.
+pub fn plan_merge(
+    registry: &MigrationRegistry,
+    app: Option<&str>,
+) -> Result<Option<MergePlan>, MigrationError> {
+    let leaves = registry.leaves(app);
+    if leaves.len() < 2 {
+        return Ok(None);
+    }
+
+    let histories = leaves
+        .iter()
+        .map(|&leaf| registry.resolve_order_for(&[leaf]))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // A branch is what one leaf's history has that no other leaf's has.
+    let branches: Vec<Vec<&str>> = histories
+        .iter()
+        .enumerate()
+        .map(|(i, history)| {
+            let others: HashSet<&str> = histories
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .flat_map(|(_, other)| other.iter().copied())
+                .collect();
+            history
+                .iter()
+                .copied()
+                .filter(|name| !others.contains(name))
+                .collect()
+        })
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (i, branch) in branches.iter().enumerate() {
+        for other in &branches[i + 1..] {
+            for &first in branch {
+                for &second in other {
+                    for conflict in find_conflicts(registry, first, second) {
+                        if !conflicts.contains(&conflict) {
+                            conflicts.push(conflict);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(Some(MergePlan {
+        app: app.map(str::to_string),
+        leaves: leaves.into_iter().map(str::to_string).collect(),
+        conflicts,
+    }))
+}
+
+fn find_conflicts(registry: &MigrationRegistry, first: &str, second: &str) -> Vec<MergeConflict> {
+    let touches = |name: &str| {
+        registry
+            .get(name)
+            .map(Migration::touches)
+            .unwrap_or_default()
+    };
+    let (first_touches, second_touches) = (touches(first), touches(second));
+
+    let mut conflicts = Vec::new();
+    for a in &first_touches {
+        for b in second_touches.iter().filter(|b| overlaps(a, b)) {
+            let column = match (a.column(), b.column()) {
+                (Some(x), Some(y)) if x == y => Some(x.to_string()),
+                _ => None,
+            };
+            conflicts.push(MergeConflict {
+                table: a.table().to_string(),
+                column,
+                first: first.to_string(),
+                second: second.to_string(),
+            });
+        }
+    }
+    conflicts
+}
+
+/// Changes to the same column, or to a table one side creates or drops.
+/// References only clash with the referenced table being created or dropped.
+fn overlaps(a: &Touch, b: &Touch) -> bool {
+    if a.table() != b.table() {
+        return false;
+    }
+    match (a, b) {
+        (Touch::Alter { column: x, .. }, Touch::Alter { column: y, .. }) => x == y,
+        (Touch::Reference { .. }, Touch::Reference { .. })
+        | (Touch::Reference { .. }, Touch::Alter { .. })
+        | (Touch::Alter { .. }, Touch::Reference { .. }) => false,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Field, FieldType};
+    use crate::operation::{
+        AddField, AddIndex, CreateTable, DropTable, Index, RemoveField, RunSql,
+    };
+
+    fn setup_registry() -> MigrationRegistry {
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_initial").operation(
+                CreateTable::new("users")
+                    .add_field(Field::new("id", FieldType::Serial).primary_key())
+                    .add_field(Field::new("email", FieldType::Text)),
+            ),
+        );
+        registry
+    }
+
+    #[test]
+    fn linear_history_needs_no_merge() {
+        let mut registry = setup_registry();
+        registry.register(Migration::new("0002_next").depends_on(&["0001_initial"]));
+
+        assert_eq!(plan_merge(&registry, None).unwrap(), None);
+    }
+
+    #[test]
+    fn merge_without_conflicts() {
+        let mut registry = setup_registry();
+        registry.register(
+            Migration::new("0002_add_name")
+                .depends_on(&["0001_initial"])
+                .operation(AddField::new("users", Field::new("name", FieldType::Text))),
+        );
+        registry.register(
+            Migration::new("0002_add_posts")
+                .depends_on(&["0001_initial"])
+                .operation(CreateTable::new("posts").field("id", FieldType::Serial)),
+        );
+
+        let plan = plan_merge(&registry, None).unwrap().unwrap();
+        assert_eq!(plan.leaves, vec!["0002_add_name", "0002_add_posts"]);
+        assert!(!plan.has_conflicts());
+        assert_eq!(plan.default_name(), "0003_merge");
+
+        let merge = plan.migration(plan.default_name());
+        assert_eq!(merge.dependencies, vec!["0002_add_name", "0002_add_posts"]);
+        assert!(merge.forward_operations().is_empty());
+
+        registry.register(merge);
+        assert_eq!(registry.leaves(None), vec!["0003_merge"]);
+        assert_eq!(registry.validate(), Ok(()));
+    }
+
+    #[test]
+    fn merge_reports_same_column_conflict() {
+        let mut registry = setup_registry();
+        registry.register(
+            Migration::new("0002_email_index")
+                .depends_on(&["0001_initial"])
+                .operation(AddIndex::new(
+                    "users",
+                    Index::new("idx_email").column("email"),
+                )),
+        );
+        registry.register(
+            Migration::new("0002_add_phone")
+                .depends_on(&["0001_initial"])
+                .operation(AddField::new("users", Field::new("phone", FieldType::Text))),
+        );
+        registry.register(
+            Migration::new("0003_drop_email")
+                .depends_on(&["0002_add_phone"])
+                .operation(RemoveField::new("users", "email")),
+        );
+
+        let plan = plan_merge(&registry, None).unwrap().unwrap();
+        assert_eq!(plan.leaves, vec!["0002_email_index", "0003_drop_email"]);
+        assert_eq!(plan.default_name(), "0004_merge");
+        assert_eq!(
+            plan.conflicts,
+            vec![MergeConflict {
+                table: "users".to_string(),
+                column: Some("email".to_string()),
+                first: "0002_email_index".to_string(),
+                second: "0003_drop_email".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn merge_reports_dropped_table_conflict() {
+        let mut registry = setup_registry();
+        registry.register(
+            Migration::new("0002_drop_users")
+                .depends_on(&["0001_initial"])
+                .operation(DropTable::new("users")),
+        );
+        registry.register(
+            Migration::new("0002_add_posts")
+                .depends_on(&["0001_initial"])
+                .operation(CreateTable::new("posts").add_field(
+                    Field::new("author_id", FieldType::Integer).references("users", "id"),
+                )),
+        );
+
+        let plan = plan_merge(&registry, None).unwrap().unwrap();
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].table, "users");
+        assert_eq!(plan.conflicts[0].column, None);
+    }
+
+    #[test]
+    fn merge_ignores_raw_sql() {
+        let mut registry = setup_registry();
+        registry.register(
+            Migration::new("0002_a")
+                .depends_on(&["0001_initial"])
+                .operation(RunSql::new("UPDATE users SET email = lower(email)")),
+        );
+        registry.register(
+            Migration::new("0002_b")
+                .depends_on(&["0001_initial"])
+                .operation(RunSql::new("DELETE FROM users")),
+        );
+
+        let plan = plan_merge(&registry, None).unwrap().unwrap();
+        assert!(!plan.has_conflicts());
+    }
+
+    #[test]
+    fn merge_within_app() {
+        let mut registry = MigrationRegistry::new();
+        registry.register_app(
+            "auth",
+            [
+                Migration::new("0001_initial"),
+                Migration::new("0002_a").depends_on(&["0001_initial"]),
+                Migration::new("0002_b").depends_on(&["0001_initial"]),
+            ],
+        );
+
+        assert_eq!(plan_merge(&registry, None).unwrap(), None);
+
+        let plan = plan_merge(&registry, Some("auth")).unwrap().unwrap();
+        assert_eq!(plan.leaves, vec!["auth.0002_a", "auth.0002_b"]);
+        assert_eq!(plan.default_name(), "0003_merge");
+
+        registry.register(plan.migration(plan.default_name()));
+        assert_eq!(registry.leaves(Some("auth")), vec!["auth.0003_merge"]);
+    }
+}
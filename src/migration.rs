@@ -1,11 +1,28 @@
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
 
 use crate::backend::Backend;
-use crate::operation::Operation;
+use crate::operation::{Operation, RunContext, Statement, Touch};
+
+/// Separates the app from the migration name in qualified names (`auth.0001_initial`).
+pub const APP_SEPARATOR: char = '.';
+
+/// Stands for an app's most recent migration(s) in a qualified dependency
+/// (`auth.__latest__`). Prefer [`Migration::depends_on_latest`] over writing it by hand.
+pub const LATEST: &str = "__latest__";
+
+fn qualify(app: &str, name: &str) -> String {
+    format!("{}{}{}", app, APP_SEPARATOR, name)
+}
 
 pub struct Migration {
-    pub name: &'static str,
-    pub dependencies: &'static [&'static str],
+    pub name: Cow<'static, str>,
+    /// App namespace, set by [`Migration::in_app`] or [`MigrationRegistry::register_app`].
+    pub app: Option<Cow<'static, str>>,
+    pub dependencies: Vec<Cow<'static, str>>,
+    /// Migrations that must run after this one, declared from this side.
+    pub run_before: Vec<Cow<'static, str>>,
     forward: Vec<Box<dyn Operation>>,
     backward: Option<Vec<Box<dyn Operation>>>,
     atomic: bool,
@@ -15,7 +32,9 @@ impl std::fmt::Debug for Migration {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Migration")
             .field("name", &self.name)
+            .field("app", &self.app)
             .field("dependencies", &self.dependencies)
+            .field("run_before", &self.run_before)
             .field("forward", &format!("[{} operations]", self.forward.len()))
             .field(
                 "backward",
@@ -30,21 +49,82 @@ impl std::fmt::Debug for Migration {
 }
 
 impl Migration {
-    pub fn new(name: &'static str) -> Self {
+    /// Create a migration. Accepts static literals as well as owned names,
+    /// so migrations can be built at runtime (e.g. loaded from files).
+    pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
         Self {
-            name,
-            dependencies: &[],
+            name: name.into(),
+            app: None,
+            dependencies: Vec::new(),
+            run_before: Vec::new(),
             forward: Vec::new(),
             backward: None,
             atomic: true,
         }
     }
 
-    pub fn depends_on(mut self, dependencies: &'static [&'static str]) -> Self {
-        self.dependencies = dependencies;
+    /// Set the dependencies by static name, replacing any declared before.
+    /// Use [`Migration::dependency`] or [`Migration::dependencies`] to add
+    /// to them instead.
+    pub fn depends_on(mut self, dependencies: &[&'static str]) -> Self {
+        self.dependencies = dependencies.iter().map(|&dep| Cow::Borrowed(dep)).collect();
+        self
+    }
+
+    /// Add a single dependency, which may be an owned name.
+    pub fn dependency(mut self, dependency: impl Into<Cow<'static, str>>) -> Self {
+        self.dependencies.push(dependency.into());
+        self
+    }
+
+    /// Add dependencies from any collection of names, owned or static.
+    pub fn dependencies<I, D>(mut self, dependencies: I) -> Self
+    where
+        I: IntoIterator<Item = D>,
+        D: Into<Cow<'static, str>>,
+    {
+        self.dependencies
+            .extend(dependencies.into_iter().map(Into::into));
+        self
+    }
+
+    /// Declare migrations that must run after this one. This adds this
+    /// migration to their dependencies without touching them, so a library
+    /// can slot its migration in before one of the application's.
+    pub fn run_before(mut self, migrations: &[&'static str]) -> Self {
+        self.run_before
+            .extend(migrations.iter().map(|&name| Cow::Borrowed(name)));
         self
     }
 
+    /// Place this migration in an app namespace. It is registered as
+    /// `app.name`, and bare dependency names resolve within the app first,
+    /// so several apps can ship migrations with the same short names.
+    pub fn in_app(mut self, app: impl Into<Cow<'static, str>>) -> Self {
+        self.app = Some(app.into());
+        self
+    }
+
+    /// Add a dependency on a migration of another app.
+    pub fn depends_on_app(self, app: &str, name: &str) -> Self {
+        self.dependency(qualify(app, name))
+    }
+
+    /// Add a dependency on the latest migration(s) of another app, i.e. the
+    /// ones no other migration of that app depends on.
+    pub fn depends_on_latest(self, app: &str) -> Self {
+        self.dependency(qualify(app, LATEST))
+    }
+
+    /// The name this migration is registered and recorded under:
+    /// `app.name` inside an app, otherwise just `name`.
+    pub fn qualified_name(&self) -> Cow<'static, str> {
+        match self.app {
+            Some(ref app) => Cow::Owned(qualify(app, &self.name)),
+            None => self.name.clone(),
+        }
+    }
+
     /// Set whether this migration should run inside a transaction.
     /// Defaults to `true`. Set to `false` for operations that cannot
     /// run in transactions (e.g., `CREATE INDEX CONCURRENTLY` in PostgreSQL).
@@ -53,9 +133,15 @@ impl Migration {
         self
     }
 
-    /// Check if this migration should run atomically (in a transaction).
+    /// Check if this migration should run atomically (in a transaction):
+    /// it wasn't marked non-atomic and all its operations are atomic.
     pub fn is_atomic(&self) -> bool {
         self.atomic
+            && self
+                .forward
+                .iter()
+                .chain(self.backward.iter().flatten())
+                .all(|op| op.is_atomic())
     }
 
     /// Add an operation with automatic reverse derivation.
@@ -117,6 +203,80 @@ impl Migration {
         }
     }
 
+    /// Like [`Migration::backward_sql`], but `None` rather than a panic when
+    /// an operation can't build its reverse for `backend`.
+    pub fn try_backward_sql(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        let sqls: Option<Vec<Vec<String>>> = if let Some(ref backward) = self.backward {
+            backward.iter().map(|op| op.try_forward(backend)).collect()
+        } else if self.is_reversible() {
+            self.forward
+                .iter()
+                .rev()
+                .map(|op| op.try_backward(backend))
+                .collect()
+        } else {
+            None
+        };
+        sqls.map(|sqls| sqls.concat())
+    }
+
+    /// Whether any operation runs code (see [`Operation::runs_code`]).
+    pub fn runs_code(&self) -> bool {
+        self.forward
+            .iter()
+            .chain(self.backward.iter().flatten())
+            .any(|op| op.runs_code())
+    }
+
+    /// Fail if any operation can't run on `backend` (see
+    /// [`Operation::check`]).
+    pub fn check(&self, backend: &dyn Backend) -> Result<(), String> {
+        self.forward
+            .iter()
+            .chain(self.backward.iter().flatten())
+            .try_for_each(|op| op.check(backend))
+    }
+
+    /// Apply the forward operations in order, executing each one's
+    /// statements and then its code.
+    pub fn apply(
+        &self,
+        backend: &dyn Backend,
+        executor: &mut dyn FnMut(&Statement) -> Result<(), String>,
+    ) -> Result<(), String> {
+        self.check(backend)?;
+        run_forward_ops(&self.forward, backend, executor)
+    }
+
+    /// Roll back like [`Migration::backward_sql`] would, running code
+    /// alongside the SQL. Fails if the migration isn't reversible.
+    pub fn unapply(
+        &self,
+        backend: &dyn Backend,
+        executor: &mut dyn FnMut(&Statement) -> Result<(), String>,
+    ) -> Result<(), String> {
+        self.check(backend)?;
+        if let Some(ref backward) = self.backward {
+            return run_forward_ops(backward, backend, executor);
+        }
+        if !self.is_reversible() {
+            return Err(format!("Migration {} is not reversible", self.name));
+        }
+        for op in self.forward.iter().rev() {
+            op.backward_statements(backend)
+                .unwrap_or_default()
+                .iter()
+                .try_for_each(&mut *executor)?;
+            op.run_backward(&mut RunContext::new(backend, executor))?;
+        }
+        Ok(())
+    }
+
+    /// Tables and columns touched by the forward operations.
+    pub fn touches(&self) -> Vec<Touch> {
+        self.forward.iter().flat_map(|op| op.touches()).collect()
+    }
+
     /// Access forward operations (for inspection).
     pub fn forward_operations(&self) -> &[Box<dyn Operation>] {
         &self.forward
@@ -128,10 +288,32 @@ impl Migration {
     }
 }
 
+fn run_forward_ops(
+    ops: &[Box<dyn Operation>],
+    backend: &dyn Backend,
+    executor: &mut dyn FnMut(&Statement) -> Result<(), String>,
+) -> Result<(), String> {
+    for op in ops {
+        op.forward_statements(backend)
+            .iter()
+            .try_for_each(&mut *executor)?;
+        op.run_forward(&mut RunContext::new(backend, executor))?;
+    }
+    Ok(())
+}
+
+/// Migrations that declared `run_before` on each target, keyed by the
+/// target's qualified name.
+type RunBeforeEdges = HashMap<String, Vec<String>>;
+
 #[derive(Default)]
 pub struct MigrationRegistry {
-    migrations: HashMap<&'static str, Migration>,
-    order: Vec<&'static str>,
+    migrations: HashMap<Cow<'static, str>, Migration>,
+    order: Vec<Cow<'static, str>>,
+    duplicates: Vec<Cow<'static, str>>,
+    /// Built on first use and dropped whenever the registry changes, since
+    /// `latest` targets depend on every registered migration.
+    run_before_edges: OnceLock<Result<RunBeforeEdges, MigrationError>>,
 }
 
 impl MigrationRegistry {
@@ -139,38 +321,184 @@ impl MigrationRegistry {
         Self::default()
     }
 
+    /// Register a migration. Registering a name twice replaces the earlier
+    /// migration and is reported by [`MigrationRegistry::validate`].
     pub fn register(&mut self, migration: Migration) {
-        let name = migration.name;
-        self.migrations.insert(name, migration);
-        self.order.push(name);
+        self.run_before_edges.take();
+        let name = migration.qualified_name();
+        if self.migrations.insert(name.clone(), migration).is_some() {
+            self.duplicates.push(name);
+        } else {
+            self.order.push(name);
+        }
+    }
+
+    /// Register several migrations in order.
+    pub fn register_all(&mut self, migrations: impl IntoIterator<Item = Migration>) {
+        for migration in migrations {
+            self.register(migration);
+        }
+    }
+
+    /// Register a set of migrations in order under an app namespace.
+    pub fn register_app(
+        &mut self,
+        app: impl Into<Cow<'static, str>>,
+        migrations: impl IntoIterator<Item = Migration>,
+    ) {
+        let app = app.into();
+        for migration in migrations {
+            self.register(migration.in_app(app.clone()));
+        }
     }
 
+    /// Look up a migration by its qualified name.
     pub fn get(&self, name: &str) -> Option<&Migration> {
         self.migrations.get(name)
     }
 
+    /// Add a dependency to a registered migration. Names and order stay as
+    /// registered, so this is the only change allowed after registering.
+    pub fn add_dependency(
+        &mut self,
+        name: &str,
+        dependency: impl Into<Cow<'static, str>>,
+    ) -> Result<(), MigrationError> {
+        let migration = self
+            .migrations
+            .get_mut(name)
+            .ok_or_else(|| MigrationError::NotFound(name.to_string()))?;
+        migration.dependencies.push(dependency.into());
+        self.run_before_edges.take();
+        Ok(())
+    }
+
     pub fn all(&self) -> impl Iterator<Item = &Migration> {
         self.order
             .iter()
             .filter_map(|name| self.migrations.get(name))
     }
 
-    pub fn resolve_order(&self) -> Result<Vec<&'static str>, MigrationError> {
-        let mut resolved: Vec<&'static str> = Vec::new();
-        let mut seen: HashMap<&'static str, bool> = HashMap::new();
+    /// App namespaces in registration order.
+    pub fn apps(&self) -> Vec<&str> {
+        let mut apps: Vec<&str> = Vec::new();
+        for app in self.all().filter_map(|m| m.app.as_deref()) {
+            if !apps.contains(&app) {
+                apps.push(app);
+            }
+        }
+        apps
+    }
 
-        for name in &self.order {
+    /// Qualified names of the migrations registered under `app`, in registration order.
+    pub fn app_migration_names(&self, app: &str) -> Vec<&str> {
+        self.order
+            .iter()
+            .filter(|name| {
+                self.migrations
+                    .get(name.as_ref())
+                    .is_some_and(|m| m.app.as_deref() == Some(app))
+            })
+            .map(|name| name.as_ref())
+            .collect()
+    }
+
+    /// Qualified names of the migrations `name` depends on directly, with
+    /// app-relative and `latest` dependencies resolved. Includes migrations
+    /// that declared `run_before` on `name`.
+    pub fn resolved_dependencies(&self, name: &str) -> Result<Vec<&str>, MigrationError> {
+        let migration = self
+            .migrations
+            .get(name)
+            .ok_or_else(|| MigrationError::NotFound(name.to_string()))?;
+
+        let mut resolved = Vec::new();
+        for dep in &migration.dependencies {
+            resolved.extend(self.resolve_dependency(migration, dep)?);
+        }
+
+        let edges = self
+            .run_before_edges
+            .get_or_init(|| self.build_run_before_edges())
+            .as_ref()
+            .map_err(Clone::clone)?;
+        if let Some(before) = edges.get(name) {
+            resolved.extend(before.iter().map(String::as_str));
+        }
+
+        Ok(resolved)
+    }
+
+    fn build_run_before_edges(&self) -> Result<RunBeforeEdges, MigrationError> {
+        let mut edges = RunBeforeEdges::new();
+        for other_name in &self.order {
+            let other = &self.migrations[other_name];
+            for target in &other.run_before {
+                for resolved in self.resolve_dependency(other, target)? {
+                    edges
+                        .entry(resolved.to_string())
+                        .or_default()
+                        .push(other_name.to_string());
+                }
+            }
+        }
+        Ok(edges)
+    }
+
+    pub fn resolve_order(&self) -> Result<Vec<&str>, MigrationError> {
+        self.resolve_from(self.order.iter().map(|name| name.as_ref()))
+    }
+
+    /// Resolve the given migrations and everything they depend on, in
+    /// dependency order.
+    pub fn resolve_order_for(&self, names: &[&str]) -> Result<Vec<&str>, MigrationError> {
+        let roots = names
+            .iter()
+            .map(|&name| {
+                self.key(name)
+                    .ok_or_else(|| MigrationError::NotFound(name.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.resolve_from(roots.into_iter())
+    }
+
+    /// The given migrations and everything that depends on them, directly or
+    /// transitively, in dependency order.
+    pub fn dependents(&self, names: &[&str]) -> Result<Vec<&str>, MigrationError> {
+        let mut affected: Vec<&str> = Vec::new();
+
+        for name in self.resolve_order()? {
+            let depends_on_affected = self
+                .resolved_dependencies(name)?
+                .iter()
+                .any(|dep| affected.contains(dep));
+            if names.contains(&name) || depends_on_affected {
+                affected.push(name);
+            }
+        }
+
+        Ok(affected)
+    }
+
+    fn resolve_from<'r>(
+        &'r self,
+        roots: impl Iterator<Item = &'r str>,
+    ) -> Result<Vec<&'r str>, MigrationError> {
+        let mut resolved: Vec<&str> = Vec::new();
+        let mut seen: HashMap<&str, bool> = HashMap::new();
+
+        for name in roots {
             self.resolve_deps(name, &mut resolved, &mut seen)?;
         }
 
         Ok(resolved)
     }
 
-    fn resolve_deps(
-        &self,
-        name: &'static str,
-        resolved: &mut Vec<&'static str>,
-        seen: &mut HashMap<&'static str, bool>,
+    fn resolve_deps<'r>(
+        &'r self,
+        name: &'r str,
+        resolved: &mut Vec<&'r str>,
+        seen: &mut HashMap<&'r str, bool>,
     ) -> Result<(), MigrationError> {
         if let Some(&in_progress) = seen.get(name) {
             if in_progress {
@@ -181,12 +509,7 @@ impl MigrationRegistry {
 
         seen.insert(name, true);
 
-        let migration = self
-            .migrations
-            .get(name)
-            .ok_or_else(|| MigrationError::NotFound(name.to_string()))?;
-
-        for dep in migration.dependencies {
+        for dep in self.resolved_dependencies(name)? {
             self.resolve_deps(dep, resolved, seen)?;
         }
 
@@ -199,6 +522,169 @@ impl MigrationRegistry {
         Ok(())
     }
 
+    /// Resolve one declared dependency of `migration` to registry keys.
+    /// Bare names inside an app prefer the app's own migrations.
+    fn resolve_dependency(
+        &self,
+        migration: &Migration,
+        dep: &str,
+    ) -> Result<Vec<&str>, MigrationError> {
+        let latest_of = dep
+            .strip_suffix(LATEST)
+            .and_then(|app| app.strip_suffix(APP_SEPARATOR));
+        if let Some(app) = latest_of {
+            let leaves = self.app_leaves(app);
+            if leaves.is_empty() {
+                return Err(MigrationError::NotFound(dep.to_string()));
+            }
+            return Ok(leaves);
+        }
+
+        if let Some(ref app) = migration.app {
+            if let Some(key) = self.key(&qualify(app, dep)) {
+                return Ok(vec![key]);
+            }
+        }
+
+        self.key(dep)
+            .map(|key| vec![key])
+            .ok_or_else(|| MigrationError::NotFound(dep.to_string()))
+    }
+
+    /// Migrations of `app` that no other migration of the same app depends on.
+    fn app_leaves(&self, app: &str) -> Vec<&str> {
+        let members = self.app_migration_names(app);
+        let in_app = |dep: &str| self.key(&qualify(app, dep)).or_else(|| self.key(dep));
+
+        let mut depended: HashSet<&str> = HashSet::new();
+        for &name in &members {
+            let migration = &self.migrations[name];
+            depended.extend(migration.dependencies.iter().filter_map(|dep| in_app(dep)));
+            let runs_before_member = migration
+                .run_before
+                .iter()
+                .filter_map(|target| in_app(target))
+                .any(|target| members.contains(&target));
+            if runs_before_member {
+                depended.insert(name);
+            }
+        }
+
+        members
+            .into_iter()
+            .filter(|name| !depended.contains(name))
+            .collect()
+    }
+
+    /// Check the whole registry and report every problem at once: duplicate
+    /// names, dangling dependencies, cycles and divergent leaves.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors: Vec<ValidationError> = self
+            .duplicates
+            .iter()
+            .map(|name| ValidationError::Duplicate(name.to_string()))
+            .collect();
+
+        let (graph, missing) = self.dependency_graph();
+        errors.extend(missing);
+        errors.extend(
+            find_cycles(&self.order, &graph)
+                .into_iter()
+                .map(ValidationError::Cycle),
+        );
+
+        let mut namespaces: Vec<Option<&str>> = vec![None];
+        namespaces.extend(self.apps().into_iter().map(Some));
+        for app in namespaces {
+            let leaves = self.leaves_in(&graph, app);
+            if leaves.len() > 1 {
+                errors.push(ValidationError::MultipleLeaves {
+                    app: app.map(str::to_string),
+                    leaves: leaves.into_iter().map(str::to_string).collect(),
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Migrations in `app` (or outside any app for `None`) that no other
+    /// migration there depends on. More than one means the history diverged.
+    pub fn leaves(&self, app: Option<&str>) -> Vec<&str> {
+        let (graph, _) = self.dependency_graph();
+        self.leaves_in(&graph, app)
+    }
+
+    fn leaves_in<'r>(
+        &'r self,
+        graph: &HashMap<&'r str, Vec<&'r str>>,
+        app: Option<&str>,
+    ) -> Vec<&'r str> {
+        let members: Vec<&str> = self
+            .order
+            .iter()
+            .map(|name| name.as_ref())
+            .filter(|name| self.migrations[*name].app.as_deref() == app)
+            .collect();
+        let depended: HashSet<&str> = members
+            .iter()
+            .flat_map(|name| &graph[name])
+            .copied()
+            .collect();
+
+        members
+            .into_iter()
+            .filter(|name| !depended.contains(name))
+            .collect()
+    }
+
+    /// Resolved dependency edges for every migration, including `run_before`
+    /// edges, skipping (and reporting) references that don't resolve.
+    fn dependency_graph(&self) -> (HashMap<&str, Vec<&str>>, Vec<ValidationError>) {
+        let mut graph: HashMap<&str, Vec<&str>> = self
+            .order
+            .iter()
+            .map(|name| (name.as_ref(), Vec::new()))
+            .collect();
+        let mut missing = Vec::new();
+
+        for name in &self.order {
+            let migration = &self.migrations[name];
+            let declared = migration.dependencies.iter().map(|dep| (dep, false));
+            let reverse = migration.run_before.iter().map(|target| (target, true));
+
+            for (reference, is_run_before) in declared.chain(reverse) {
+                let Ok(keys) = self.resolve_dependency(migration, reference) else {
+                    missing.push(ValidationError::MissingDependency {
+                        migration: name.to_string(),
+                        dependency: reference.to_string(),
+                    });
+                    continue;
+                };
+                for key in keys {
+                    if is_run_before {
+                        graph.entry(key).or_default().push(name.as_ref());
+                    } else {
+                        graph.entry(name.as_ref()).or_default().push(key);
+                    }
+                }
+            }
+        }
+
+        (graph, missing)
+    }
+
+    /// Borrow the registry's own copy of a key.
+    fn key(&self, name: &str) -> Option<&str> {
+        self.migrations
+            .get_key_value(name)
+            .map(|(key, _)| key.as_ref())
+    }
+
     pub fn len(&self) -> usize {
         self.migrations.len()
     }
@@ -213,6 +699,11 @@ pub enum MigrationError {
     NotFound(String),
     CircularDependency(String),
     NotReversible(String),
+    /// A pending migration sorts before `applied`, which already ran.
+    OutOfOrder {
+        migration: String,
+        applied: String,
+    },
     ExecutionFailed {
         migration: String,
         error: String,
@@ -231,6 +722,11 @@ impl std::fmt::Display for MigrationError {
             MigrationError::NotReversible(name) => {
                 write!(f, "Migration is not reversible: {}", name)
             }
+            MigrationError::OutOfOrder { migration, applied } => write!(
+                f,
+                "Migration {} is out of order: {} is already applied",
+                migration, applied
+            ),
             MigrationError::ExecutionFailed {
                 migration,
                 error,
@@ -254,12 +750,106 @@ impl std::fmt::Display for MigrationError {
 
 impl std::error::Error for MigrationError {}
 
+/// A problem found by [`MigrationRegistry::validate`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// The same name was registered more than once; the last registration won.
+    Duplicate(String),
+    /// A dependency or `run_before` target that isn't registered.
+    MissingDependency {
+        migration: String,
+        dependency: String,
+    },
+    /// A dependency cycle. Each migration depends on the next, and the
+    /// path ends where it started.
+    Cycle(Vec<String>),
+    /// More than one migration that nothing else in its app depends on,
+    /// usually two branches created in parallel. `app` is `None` for
+    /// migrations outside any app.
+    MultipleLeaves {
+        app: Option<String>,
+        leaves: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::Duplicate(name) => write!(f, "Duplicate migration: {}", name),
+            ValidationError::MissingDependency {
+                migration,
+                dependency,
+            } => write!(
+                f,
+                "Migration {} references missing migration: {}",
+                migration, dependency
+            ),
+            ValidationError::Cycle(path) => {
+                write!(f, "Circular dependency: {}", path.join(" -> "))
+            }
+            ValidationError::MultipleLeaves { app, leaves } => {
+                let scope = match app {
+                    Some(app) => format!(" in app {}", app),
+                    None => String::new(),
+                };
+                write!(
+                    f,
+                    "Multiple leaf migrations{}: {}",
+                    scope,
+                    leaves.join(", ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Depth-first search for cycles, returning each one as a closed path.
+fn find_cycles(roots: &[Cow<'static, str>], graph: &HashMap<&str, Vec<&str>>) -> Vec<Vec<String>> {
+    fn visit<'g>(
+        name: &'g str,
+        graph: &HashMap<&str, Vec<&'g str>>,
+        in_progress: &mut HashMap<&'g str, bool>,
+        stack: &mut Vec<&'g str>,
+        cycles: &mut Vec<Vec<String>>,
+    ) {
+        match in_progress.get(name) {
+            Some(true) => {
+                let start = stack.iter().position(|&n| n == name).unwrap_or(0);
+                let mut cycle: Vec<String> = stack[start..].iter().map(|n| n.to_string()).collect();
+                cycle.push(name.to_string());
+                cycles.push(cycle);
+                return;
+            }
+            Some(false) => return,
+            None => {}
+        }
+
+        in_progress.insert(name, true);
+        stack.push(name);
+        for &dep in graph.get(name).into_iter().flatten() {
+            visit(dep, graph, in_progress, stack, cycles);
+        }
+        stack.pop();
+        in_progress.insert(name, false);
+    }
+
+    let mut in_progress = HashMap::new();
+    let mut stack = Vec::new();
+    let mut cycles = Vec::new();
+    for name in roots {
+        visit(name, graph, &mut in_progress, &mut stack, &mut cycles);
+    }
+    cycles
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::backend::Sqlite;
     use crate::field::{Field, FieldType};
-    use crate::operation::{CreateTable, DropTable, RunSql};
+    use crate::operation::{Backfill, CreateTable, DropTable, RunSql};
 
     #[test]
     fn migration_builder() {
@@ -272,6 +862,29 @@ mod tests {
         assert_eq!(migration.forward_operations().len(), 1);
     }
 
+    #[test]
+    fn migration_with_owned_names() {
+        let names: Vec<String> = (1..=2).map(|i| format!("{:04}_generated", i)).collect();
+        let migration = Migration::new(format!("{:04}_generated", 3))
+            .dependencies(names.clone())
+            .dependency("0000_base");
+
+        assert_eq!(migration.name, "0003_generated");
+        assert_eq!(
+            migration.dependencies,
+            vec!["0001_generated", "0002_generated", "0000_base"]
+        );
+    }
+
+    #[test]
+    fn depends_on_replaces_dependencies() {
+        let migration = Migration::new("0003_c")
+            .depends_on(&["0001_a"])
+            .depends_on(&["0002_b"])
+            .dependency("0000_base");
+        assert_eq!(migration.dependencies, vec!["0002_b", "0000_base"]);
+    }
+
     #[test]
     fn migration_auto_reverse() {
         let migration = Migration::new("0001_create_users").operation(
@@ -360,6 +973,21 @@ mod tests {
         assert_eq!(order[1], "0002_second");
     }
 
+    #[test]
+    fn registry_resolve_order_with_owned_names() {
+        let mut registry = MigrationRegistry::new();
+        for i in (1..=3).rev() {
+            let mut migration = Migration::new(format!("{:04}_step", i));
+            if i > 1 {
+                migration = migration.dependency(format!("{:04}_step", i - 1));
+            }
+            registry.register(migration);
+        }
+
+        let order = registry.resolve_order().unwrap();
+        assert_eq!(order, vec!["0001_step", "0002_step", "0003_step"]);
+    }
+
     #[test]
     fn registry_detects_circular_dependency() {
         let mut registry = MigrationRegistry::new();
@@ -435,6 +1063,18 @@ mod tests {
         assert!(debug.contains("1 operations"));
     }
 
+    #[test]
+    fn registry_register_all() {
+        let mut registry = MigrationRegistry::new();
+        registry.register_all(vec![
+            Migration::new("0001_a"),
+            Migration::new("0002_b").depends_on(&["0001_a"]),
+        ]);
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.resolve_order().unwrap(), vec!["0001_a", "0002_b"]);
+    }
+
     #[test]
     fn registry_len_and_is_empty() {
         let mut registry = MigrationRegistry::new();
@@ -452,7 +1092,7 @@ mod tests {
         registry.register(Migration::new("0001_a"));
         registry.register(Migration::new("0002_b"));
 
-        let names: Vec<_> = registry.all().map(|m| m.name).collect();
+        let names: Vec<_> = registry.all().map(|m| m.name.as_ref()).collect();
         assert_eq!(names, vec!["0001_a", "0002_b"]);
     }
 
@@ -485,6 +1125,14 @@ mod tests {
         assert!(!migration.is_atomic());
     }
 
+    #[test]
+    fn non_atomic_operation_makes_migration_non_atomic() {
+        let migration = Migration::new("test")
+            .operation(CreateTable::new("a"))
+            .operation(Backfill::new("a", "b = 1", 1..=10));
+        assert!(!migration.is_atomic());
+    }
+
     // Complex dependency graph tests
 
     #[test]
@@ -627,4 +1275,402 @@ mod tests {
         assert!(order.contains(&"posts"));
         assert!(order.contains(&"comments"));
     }
+
+    fn setup_apps() -> MigrationRegistry {
+        let mut registry = MigrationRegistry::new();
+        registry.register_app(
+            "auth",
+            [
+                Migration::new("0001_initial"),
+                Migration::new("0002_groups").depends_on(&["0001_initial"]),
+            ],
+        );
+        registry.register_app(
+            "billing",
+            [
+                Migration::new("0001_initial").depends_on_latest("auth"),
+                Migration::new("0002_invoices")
+                    .depends_on(&["0001_initial"])
+                    .depends_on_app("auth", "0001_initial"),
+            ],
+        );
+        registry
+    }
+
+    #[test]
+    fn app_migrations_are_qualified() {
+        let registry = setup_apps();
+
+        assert_eq!(registry.len(), 4);
+        assert_eq!(registry.apps(), vec!["auth", "billing"]);
+        assert_eq!(
+            registry.app_migration_names("billing"),
+            vec!["billing.0001_initial", "billing.0002_invoices"]
+        );
+
+        let migration = registry.get("auth.0001_initial").unwrap();
+        assert_eq!(migration.name, "0001_initial");
+        assert_eq!(migration.app.as_deref(), Some("auth"));
+        assert!(registry.get("0001_initial").is_none());
+    }
+
+    #[test]
+    fn app_dependencies_resolve_within_app_first() {
+        let registry = setup_apps();
+
+        assert_eq!(
+            registry
+                .resolved_dependencies("billing.0002_invoices")
+                .unwrap(),
+            vec!["billing.0001_initial", "auth.0001_initial"]
+        );
+        assert_eq!(
+            registry
+                .resolved_dependencies("billing.0001_initial")
+                .unwrap(),
+            vec!["auth.0002_groups"]
+        );
+    }
+
+    #[test]
+    fn app_resolve_order_across_apps() {
+        let registry = setup_apps();
+
+        assert_eq!(
+            registry.resolve_order().unwrap(),
+            vec![
+                "auth.0001_initial",
+                "auth.0002_groups",
+                "billing.0001_initial",
+                "billing.0002_invoices"
+            ]
+        );
+    }
+
+    #[test]
+    fn app_latest_depends_on_every_leaf() {
+        let mut registry = MigrationRegistry::new();
+        registry.register_app(
+            "auth",
+            [
+                Migration::new("0001_initial"),
+                Migration::new("0002_groups").depends_on(&["0001_initial"]),
+                Migration::new("0002_tokens").depends_on(&["0001_initial"]),
+            ],
+        );
+        registry.register(Migration::new("0001_app").depends_on_latest("auth"));
+
+        assert_eq!(
+            registry.resolved_dependencies("0001_app").unwrap(),
+            vec!["auth.0002_groups", "auth.0002_tokens"]
+        );
+    }
+
+    #[test]
+    fn app_latest_of_unknown_app() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_app").depends_on_latest("auth"));
+
+        let result = registry.resolve_order();
+        assert_eq!(
+            result,
+            Err(MigrationError::NotFound("auth.__latest__".to_string()))
+        );
+    }
+
+    #[test]
+    fn app_bare_dependency_falls_back_to_global() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0000_extensions"));
+        registry.register(
+            Migration::new("0001_initial")
+                .in_app("auth")
+                .depends_on(&["0000_extensions"]),
+        );
+
+        assert_eq!(
+            registry.resolve_order().unwrap(),
+            vec!["0000_extensions", "auth.0001_initial"]
+        );
+    }
+
+    #[test]
+    fn registry_resolve_order_for_subset() {
+        let registry = setup_apps();
+
+        assert_eq!(
+            registry.resolve_order_for(&["auth.0002_groups"]).unwrap(),
+            vec!["auth.0001_initial", "auth.0002_groups"]
+        );
+        assert!(matches!(
+            registry.resolve_order_for(&["missing"]),
+            Err(MigrationError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn registry_dependents() {
+        let registry = setup_apps();
+
+        assert_eq!(
+            registry.dependents(&["auth.0002_groups"]).unwrap(),
+            vec![
+                "auth.0002_groups",
+                "billing.0001_initial",
+                "billing.0002_invoices"
+            ]
+        );
+    }
+
+    #[test]
+    fn registry_run_before() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_users"));
+        registry.register(Migration::new("0002_posts").depends_on(&["0001_users"]));
+        registry.register(
+            Migration::new("audit_0001")
+                .depends_on(&["0001_users"])
+                .run_before(&["0002_posts"]),
+        );
+
+        assert_eq!(
+            registry.resolved_dependencies("0002_posts").unwrap(),
+            vec!["0001_users", "audit_0001"]
+        );
+        assert_eq!(
+            registry.resolve_order().unwrap(),
+            vec!["0001_users", "audit_0001", "0002_posts"]
+        );
+    }
+
+    #[test]
+    fn run_before_edges_follow_registry_changes() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_users"));
+        assert!(registry
+            .resolved_dependencies("0001_users")
+            .unwrap()
+            .is_empty());
+
+        registry.register(Migration::new("seed").run_before(&["0001_users"]));
+        assert_eq!(
+            registry.resolved_dependencies("0001_users").unwrap(),
+            vec!["seed"]
+        );
+
+        registry.register_app("blog", [Migration::new("0001_posts")]);
+        registry.register(Migration::new("late_seed").run_before(&["blog.__latest__"]));
+        assert_eq!(
+            registry.resolved_dependencies("blog.0001_posts").unwrap(),
+            vec!["late_seed"]
+        );
+
+        // A new dependency changes which migrations are the app's latest.
+        registry.register_app("blog", [Migration::new("0002_tags")]);
+        registry
+            .add_dependency("blog.0002_tags", "0001_posts")
+            .unwrap();
+        assert!(registry
+            .resolved_dependencies("blog.0001_posts")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            registry.add_dependency("missing", "0001_users"),
+            Err(MigrationError::NotFound("missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn registry_run_before_across_apps() {
+        let mut registry = MigrationRegistry::new();
+        registry.register_app(
+            "shop",
+            [
+                Migration::new("0001_initial"),
+                Migration::new("0002_orders").depends_on(&["0001_initial"]),
+            ],
+        );
+        registry.register_app(
+            "audit",
+            [Migration::new("0001_initial").run_before(&["shop.0002_orders"])],
+        );
+
+        let order = registry.resolve_order().unwrap();
+        let pos = |n: &str| order.iter().position(|&x| x == n).unwrap();
+        assert!(pos("audit.0001_initial") < pos("shop.0002_orders"));
+    }
+
+    #[test]
+    fn registry_run_before_cycle() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("A"));
+        registry.register(Migration::new("B").depends_on(&["A"]).run_before(&["A"]));
+
+        let result = registry.resolve_order();
+        assert!(matches!(result, Err(MigrationError::CircularDependency(_))));
+    }
+
+    #[test]
+    fn registry_run_before_missing_target() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("A").run_before(&["missing"]));
+
+        let result = registry.resolve_order();
+        assert_eq!(result, Err(MigrationError::NotFound("missing".to_string())));
+    }
+
+    #[test]
+    fn app_latest_skips_migrations_running_before_others() {
+        let mut registry = MigrationRegistry::new();
+        registry.register_app(
+            "auth",
+            [
+                Migration::new("0001_initial"),
+                Migration::new("0002_tokens").run_before(&["0001_initial"]),
+            ],
+        );
+        registry.register(Migration::new("0001_app").depends_on_latest("auth"));
+
+        assert_eq!(
+            registry.resolved_dependencies("0001_app").unwrap(),
+            vec!["auth.0001_initial"]
+        );
+    }
+
+    #[test]
+    fn registry_duplicate_registered_once_in_order() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("A"));
+        registry.register(Migration::new("A").operation(RunSql::new("SELECT 1")));
+
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.all().count(), 1);
+        assert_eq!(registry.resolve_order().unwrap(), vec!["A"]);
+        assert_eq!(registry.get("A").unwrap().forward_operations().len(), 1);
+    }
+
+    #[test]
+    fn validate_valid_registry() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("0001_users"));
+        registry.register(Migration::new("0002_posts").depends_on(&["0001_users"]));
+        registry.register(
+            Migration::new("audit_0001")
+                .depends_on(&["0001_users"])
+                .run_before(&["0002_posts"]),
+        );
+
+        assert_eq!(registry.validate(), Ok(()));
+        assert_eq!(setup_apps().validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_reports_duplicates() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("A"));
+        registry.register(Migration::new("A"));
+
+        assert_eq!(
+            registry.validate(),
+            Err(vec![ValidationError::Duplicate("A".to_string())])
+        );
+    }
+
+    #[test]
+    fn validate_reports_missing_dependencies() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("A").depends_on(&["missing"]));
+        registry.register(Migration::new("B").depends_on(&["A"]).run_before(&["gone"]));
+
+        assert_eq!(
+            registry.validate(),
+            Err(vec![
+                ValidationError::MissingDependency {
+                    migration: "A".to_string(),
+                    dependency: "missing".to_string(),
+                },
+                ValidationError::MissingDependency {
+                    migration: "B".to_string(),
+                    dependency: "gone".to_string(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn validate_reports_cycle_path() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("A").depends_on(&["C"]));
+        registry.register(Migration::new("B").depends_on(&["A"]));
+        registry.register(Migration::new("C").depends_on(&["B"]));
+        registry.register(Migration::new("D").depends_on(&["C"]));
+
+        assert_eq!(
+            registry.validate(),
+            Err(vec![ValidationError::Cycle(vec![
+                "A".to_string(),
+                "C".to_string(),
+                "B".to_string(),
+                "A".to_string()
+            ])])
+        );
+    }
+
+    #[test]
+    fn validate_reports_cycle_through_run_before() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("A"));
+        registry.register(Migration::new("B").depends_on(&["A"]).run_before(&["A"]));
+
+        let errors = registry.validate().unwrap_err();
+        assert!(errors.contains(&ValidationError::Cycle(vec![
+            "A".to_string(),
+            "B".to_string(),
+            "A".to_string()
+        ])));
+    }
+
+    #[test]
+    fn validate_reports_multiple_leaves_per_app() {
+        let mut registry = MigrationRegistry::new();
+        registry.register_app(
+            "auth",
+            [
+                Migration::new("0001_initial"),
+                Migration::new("0002_groups").depends_on(&["0001_initial"]),
+                Migration::new("0002_tokens").depends_on(&["0001_initial"]),
+            ],
+        );
+        registry.register_app("blog", [Migration::new("0001_initial")]);
+
+        assert_eq!(
+            registry.validate(),
+            Err(vec![ValidationError::MultipleLeaves {
+                app: Some("auth".to_string()),
+                leaves: vec![
+                    "auth.0002_groups".to_string(),
+                    "auth.0002_tokens".to_string()
+                ],
+            }])
+        );
+    }
+
+    #[test]
+    fn validate_reports_all_problems() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(Migration::new("A"));
+        registry.register(Migration::new("A"));
+        registry.register(Migration::new("B").depends_on(&["missing"]));
+        registry.register(Migration::new("C").depends_on(&["C"]));
+
+        let errors = registry.validate().unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert_eq!(errors[0].to_string(), "Duplicate migration: A");
+        assert_eq!(
+            errors[1].to_string(),
+            "Migration B references missing migration: missing"
+        );
+        assert_eq!(errors[2].to_string(), "Circular dependency: C -> C");
+        assert_eq!(errors[3].to_string(), "Multiple leaf migrations: A, B");
+    }
 }
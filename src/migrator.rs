@@ -1,10 +1,40 @@
+use std::collections::HashMap;
+
 use crate::backend::Backend;
-use crate::migration::{MigrationError, MigrationRegistry};
+use crate::migration::{Migration, MigrationError, MigrationRegistry};
+use crate::operation::{Statement, Value};
 
 pub trait MigrationStateStore {
+    /// Applied migrations in the order they were applied, oldest first.
     fn applied_migrations(&mut self) -> Result<Vec<String>, String>;
     fn mark_applied(&mut self, name: &str) -> Result<(), String>;
     fn mark_unapplied(&mut self, name: &str) -> Result<(), String>;
+
+    /// Mark a migration applied and keep the SQL that reverses it (`None`
+    /// when it is irreversible), so it can still be rolled back after it is
+    /// removed from the registry. The default discards the SQL.
+    fn mark_applied_with_backward(
+        &mut self,
+        name: &str,
+        _backward: Option<&StoredBackward>,
+    ) -> Result<(), String> {
+        self.mark_applied(name)
+    }
+
+    /// The backward SQL stored when `name` was applied, if any.
+    fn stored_backward_sql(&mut self, _name: &str) -> Result<Option<StoredBackward>, String> {
+        Ok(None)
+    }
+}
+
+/// What a state store keeps to roll back a migration once it has left the
+/// registry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredBackward {
+    pub sql: Vec<String>,
+    /// Whether the migration was atomic, so the rollback is wrapped in a
+    /// transaction the same way.
+    pub atomic: bool,
 }
 
 pub struct MigrationPlan<'a> {
@@ -16,6 +46,116 @@ pub struct Migrator<'a, S: MigrationStateStore> {
     registry: &'a MigrationRegistry,
     backend: &'a dyn Backend,
     state: S,
+    app: Option<String>,
+    out_of_order: OutOfOrderPolicy,
+}
+
+type WarnFn = Box<dyn Fn(&str, &str) + Send + Sync>;
+
+/// What `plan_forward` does with a pending migration that sorts before one
+/// that is already applied, e.g. a hotfix merged after later migrations ran.
+#[derive(Default)]
+pub enum OutOfOrderPolicy {
+    /// Apply it like any other pending migration.
+    #[default]
+    Allow,
+    /// Apply it, but first call the callback with the migration and the
+    /// applied migration it sorts before.
+    Warn(WarnFn),
+    /// Refuse to plan with `MigrationError::OutOfOrder`.
+    Error,
+}
+
+impl OutOfOrderPolicy {
+    pub fn warn(callback: impl Fn(&str, &str) + Send + Sync + 'static) -> Self {
+        OutOfOrderPolicy::Warn(Box::new(callback))
+    }
+}
+
+/// Pending migrations in `order` paired with the first applied migration
+/// that sorts after them.
+fn out_of_order<'o>(order: &[&'o str], applied: &[String]) -> Vec<(&'o str, &'o str)> {
+    let is_applied = |name: &str| applied.iter().any(|a| a == name);
+
+    order
+        .iter()
+        .enumerate()
+        .filter(|&(_, &name)| !is_applied(name))
+        .filter_map(|(i, &name)| {
+            order[i + 1..]
+                .iter()
+                .find(|&&later| is_applied(later))
+                .map(|&later| (name, later))
+        })
+        .collect()
+}
+
+/// A state store failure, reported as a failed `state` migration.
+pub(crate) fn state_error(error: String) -> MigrationError {
+    MigrationError::ExecutionFailed {
+        migration: "state".to_string(),
+        error,
+        completed: vec![],
+    }
+}
+
+fn execution_failed(name: &str, error: String, completed: &[String]) -> MigrationError {
+    MigrationError::ExecutionFailed {
+        migration: name.to_string(),
+        error,
+        completed: completed.to_vec(),
+    }
+}
+
+/// Run one migration's `body`, wrapped in begin/commit when `wrap` is
+/// set. Failures after `begin` roll back (best effort) before returning.
+fn run_step<B, C, R>(
+    wrap: bool,
+    begin: &mut B,
+    commit: &mut C,
+    rollback: &mut R,
+    body: impl FnOnce() -> Result<(), String>,
+) -> Result<(), String>
+where
+    B: FnMut() -> Result<(), String>,
+    C: FnMut() -> Result<(), String>,
+    R: FnMut() -> Result<(), String>,
+{
+    if wrap {
+        begin().map_err(|e| format!("begin transaction: {}", e))?;
+    }
+
+    let result = body();
+    if result.is_err() {
+        if wrap {
+            let _ = rollback(); // Best effort rollback
+        }
+        return result;
+    }
+
+    if wrap {
+        if let Err(e) = commit() {
+            let _ = rollback(); // Best effort rollback
+            return Err(format!("commit transaction: {}", e));
+        }
+    }
+    Ok(())
+}
+
+/// What rolling back one migration runs.
+enum Rollback<'m> {
+    Migration(&'m Migration),
+    /// Backward SQL kept by the state store for a ghost.
+    Stored(StoredBackward),
+}
+
+impl Rollback<'_> {
+    fn is_atomic(&self) -> bool {
+        match self {
+            Rollback::Migration(migration) => migration.is_atomic(),
+            Rollback::Stored(stored) => stored.atomic,
+        }
+    }
 }
 
 impl<'a, S: MigrationStateStore> Migrator<'a, S> {
@@ -24,9 +164,79 @@ impl<'a, S: MigrationStateStore> Migrator<'a, S> {
             registry,
             backend,
             state,
+            app: None,
+            out_of_order: OutOfOrderPolicy::Allow,
         }
     }
 
+    /// Set how `plan_forward` treats out-of-order migrations. Defaults to
+    /// [`OutOfOrderPolicy::Allow`].
+    pub fn out_of_order(mut self, policy: OutOfOrderPolicy) -> Self {
+        self.out_of_order = policy;
+        self
+    }
+
+    /// Restrict planning to one app. Forward plans cover the app's migrations
+    /// and whatever they depend on in other apps; backward plans cover the
+    /// app's migrations and everything that depends on them.
+    pub fn for_app(mut self, app: impl Into<String>) -> Self {
+        self.app = Some(app.into());
+        self
+    }
+
+    fn applied(&mut self) -> Result<Vec<String>, MigrationError> {
+        self.state.applied_migrations().map_err(state_error)
+    }
+
+    /// Report every registered migration as applied or pending, in
+    /// dependency order, followed by ghosts: applied migrations the
+    /// registry doesn't know. Pending migrations that sort before an
+    /// applied one are flagged as out of order.
+    pub fn status(&mut self) -> Result<Vec<StatusEntry>, MigrationError> {
+        let order = self.registry.resolve_order()?;
+        let applied = self.applied()?;
+
+        let gaps = out_of_order(&order, &applied);
+
+        let mut entries: Vec<StatusEntry> = order
+            .iter()
+            .map(|&name| StatusEntry {
+                name: name.to_string(),
+                status: if applied.iter().any(|a| a == name) {
+                    MigrationStatus::Applied
+                } else {
+                    MigrationStatus::Pending
+                },
+                out_of_order: gaps.iter().any(|&(gap, _)| gap == name),
+            })
+            .collect();
+
+        entries.extend(
+            applied
+                .into_iter()
+                .filter(|name| self.registry.get(name).is_none())
+                .map(|name| StatusEntry {
+                    name,
+                    status: MigrationStatus::Ghost,
+                    out_of_order: false,
+                }),
+        );
+
+        Ok(entries)
+    }
+
+    /// Qualified names of the scoped app's migrations, if a scope is set.
+    fn app_scope(&self) -> Result<Option<Vec<&'a str>>, MigrationError> {
+        let Some(ref app) = self.app else {
+            return Ok(None);
+        };
+        let names = self.registry.app_migration_names(app);
+        if names.is_empty() {
+            return Err(MigrationError::NotFound(app.clone()));
+        }
+        Ok(Some(names))
+    }
+
     pub fn state(&self) -> &S {
         &self.state
     }
@@ -39,16 +249,26 @@ impl<'a, S: MigrationStateStore> Migrator<'a, S> {
         self.state
     }
 
-    pub fn plan_forward(&mut self) -> Result<Vec<&'static str>, MigrationError> {
-        let order = self.registry.resolve_order()?;
-        let applied =
-            self.state
-                .applied_migrations()
-                .map_err(|e| MigrationError::ExecutionFailed {
-                    migration: "state".to_string(),
-                    error: e,
-                    completed: vec![],
-                })?;
+    pub fn plan_forward(&mut self) -> Result<Vec<&'a str>, MigrationError> {
+        let registry = self.registry;
+        let order = match self.app_scope()? {
+            Some(names) => registry.resolve_order_for(&names)?,
+            None => registry.resolve_order()?,
+        };
+        let applied = self.applied()?;
+
+        for (migration, later) in out_of_order(&order, &applied) {
+            match self.out_of_order {
+                OutOfOrderPolicy::Allow => {}
+                OutOfOrderPolicy::Warn(ref warn) => warn(migration, later),
+                OutOfOrderPolicy::Error => {
+                    return Err(MigrationError::OutOfOrder {
+                        migration: migration.to_string(),
+                        applied: later.to_string(),
+                    })
+                }
+            }
+        }
 
         Ok(order
             .into_iter()
@@ -56,21 +276,15 @@ impl<'a, S: MigrationStateStore> Migrator<'a, S> {
             .collect())
     }
 
-    pub fn plan_backward(
-        &mut self,
-        target: Option<&str>,
-    ) -> Result<Vec<&'static str>, MigrationError> {
-        let order = self.registry.resolve_order()?;
-        let applied =
-            self.state
-                .applied_migrations()
-                .map_err(|e| MigrationError::ExecutionFailed {
-                    migration: "state".to_string(),
-                    error: e,
-                    completed: vec![],
-                })?;
-
-        let mut to_unapply: Vec<&'static str> = order
+    pub fn plan_backward(&mut self, target: Option<&str>) -> Result<Vec<&'a str>, MigrationError> {
+        let registry = self.registry;
+        let order = match self.app_scope()? {
+            Some(names) => registry.dependents(&names)?,
+            None => registry.resolve_order()?,
+        };
+        let applied = self.applied()?;
+
+        let mut to_unapply: Vec<&'a str> = order
             .iter()
             .rev()
             .filter(|name| applied.contains(&name.to_string()))
@@ -107,6 +321,9 @@ impl<'a, S: MigrationStateStore> Migrator<'a, S> {
                 .registry
                 .get(name)
                 .ok_or_else(|| MigrationError::NotFound(name.to_string()))?;
+            migration
+                .check(self.backend)
+                .map_err(|e| execution_failed(name, e, &[]))?;
 
             result.push((name.to_string(), migration.forward_sql(self.backend)));
         }
@@ -126,6 +343,9 @@ impl<'a, S: MigrationStateStore> Migrator<'a, S> {
                 .registry
                 .get(name)
                 .ok_or_else(|| MigrationError::NotFound(name.to_string()))?;
+            migration
+                .check(self.backend)
+                .map_err(|e| execution_failed(name, e, &[]))?;
 
             let sqls = migration
                 .backward_sql(self.backend)
@@ -172,6 +392,67 @@ impl<'a, S: MigrationStateStore> Migrator<'a, S> {
         B: FnMut() -> Result<(), String>,
         C: FnMut() -> Result<(), String>,
         R: FnMut() -> Result<(), String>,
+    {
+        let backend = self.backend;
+        self.apply(
+            &mut |statement| executor(&statement.inline(backend)),
+            begin,
+            commit,
+            rollback,
+        )
+    }
+
+    /// Migrate forward, passing bind values to the executor separately
+    /// instead of inlining them. Placeholders in the SQL use the backend's
+    /// syntax (`$1` on PostgreSQL, `?` elsewhere).
+    pub fn migrate_forward_with_params<F>(
+        &mut self,
+        mut executor: F,
+    ) -> Result<Vec<String>, MigrationError>
+    where
+        F: FnMut(&str, &[Value]) -> Result<(), String>,
+    {
+        self.migrate_forward_with_params_and_transactions(
+            &mut executor,
+            &mut || Ok(()),
+            &mut || Ok(()),
+            &mut || Ok(()),
+        )
+    }
+
+    /// `migrate_forward_with_transactions` with bind values passed separately.
+    pub fn migrate_forward_with_params_and_transactions<E, B, C, R>(
+        &mut self,
+        executor: &mut E,
+        begin: &mut B,
+        commit: &mut C,
+        rollback: &mut R,
+    ) -> Result<Vec<String>, MigrationError>
+    where
+        E: FnMut(&str, &[Value]) -> Result<(), String>,
+        B: FnMut() -> Result<(), String>,
+        C: FnMut() -> Result<(), String>,
+        R: FnMut() -> Result<(), String>,
+    {
+        self.apply(
+            &mut |statement| executor(&statement.sql, &statement.values),
+            begin,
+            commit,
+            rollback,
+        )
+    }
+
+    fn apply<B, C, R>(
+        &mut self,
+        executor: &mut dyn FnMut(&Statement) -> Result<(), String>,
+        begin: &mut B,
+        commit: &mut C,
+        rollback: &mut R,
+    ) -> Result<Vec<String>, MigrationError>
+    where
+        B: FnMut() -> Result<(), String>,
+        C: FnMut() -> Result<(), String>,
+        R: FnMut() -> Result<(), String>,
     {
         let to_apply = self.plan_forward()?;
         let mut applied = Vec::new();
@@ -183,52 +464,26 @@ impl<'a, S: MigrationStateStore> Migrator<'a, S> {
                 .get(name)
                 .ok_or_else(|| MigrationError::NotFound(name.to_string()))?;
 
-            let should_wrap = use_transactions && migration.is_atomic();
-
-            if should_wrap {
-                begin().map_err(|e| MigrationError::ExecutionFailed {
-                    migration: name.to_string(),
-                    error: format!("begin transaction: {}", e),
-                    completed: applied.clone(),
-                })?;
-            }
-
-            let result = (|| {
-                for sql in migration.forward_sql(self.backend) {
-                    executor(&sql)?;
-                }
-                Ok(())
-            })();
-
-            if let Err(e) = result {
-                if should_wrap {
-                    let _ = rollback(); // Best effort rollback
-                }
-                return Err(MigrationError::ExecutionFailed {
-                    migration: name.to_string(),
-                    error: e,
-                    completed: applied,
-                });
-            }
-
-            if should_wrap {
-                if let Err(e) = commit() {
-                    let _ = rollback(); // Best effort rollback
-                    return Err(MigrationError::ExecutionFailed {
-                        migration: name.to_string(),
-                        error: format!("commit transaction: {}", e),
-                        completed: applied,
-                    });
-                }
-            }
-
-            self.state
-                .mark_applied(name)
-                .map_err(|e| MigrationError::ExecutionFailed {
-                    migration: name.to_string(),
-                    error: e,
-                    completed: applied.clone(),
-                })?;
+            let wrap = use_transactions && migration.is_atomic();
+            // Code can't be replayed from the state store.
+            let backward = if migration.runs_code() {
+                None
+            } else {
+                migration
+                    .try_backward_sql(self.backend)
+                    .map(|sql| StoredBackward {
+                        sql,
+                        atomic: migration.is_atomic(),
+                    })
+            };
+            run_step(wrap, begin, commit, rollback, || {
+                migration.apply(self.backend, executor)
+            })
+            .and_then(|()| {
+                self.state
+                    .mark_applied_with_backward(name, backward.as_ref())
+            })
+            .map_err(|e| execution_failed(name, e, &applied))?;
 
             applied.push(name.to_string());
         }
@@ -269,9 +524,67 @@ impl<'a, S: MigrationStateStore> Migrator<'a, S> {
         C: FnMut() -> Result<(), String>,
         R: FnMut() -> Result<(), String>,
     {
+        let steps = self.rollback_steps(target)?;
+        let backend = self.backend;
+        self.unapply(
+            steps,
+            &mut |statement| executor(&statement.inline(backend)),
+            begin,
+            commit,
+            rollback,
+        )
+    }
+
+    /// Migrate backward, passing bind values to the executor separately.
+    pub fn migrate_backward_with_params<F>(
+        &mut self,
+        target: Option<&str>,
+        mut executor: F,
+    ) -> Result<Vec<String>, MigrationError>
+    where
+        F: FnMut(&str, &[Value]) -> Result<(), String>,
+    {
+        self.migrate_backward_with_params_and_transactions(
+            target,
+            &mut executor,
+            &mut || Ok(()),
+            &mut || Ok(()),
+            &mut || Ok(()),
+        )
+    }
+
+    /// `migrate_backward_with_transactions` with bind values passed separately.
+    pub fn migrate_backward_with_params_and_transactions<E, B, C, R>(
+        &mut self,
+        target: Option<&str>,
+        executor: &mut E,
+        begin: &mut B,
+        commit: &mut C,
+        rollback: &mut R,
+    ) -> Result<Vec<String>, MigrationError>
+    where
+        E: FnMut(&str, &[Value]) -> Result<(), String>,
+        B: FnMut() -> Result<(), String>,
+        C: FnMut() -> Result<(), String>,
+        R: FnMut() -> Result<(), String>,
+    {
+        let steps = self.rollback_steps(target)?;
+        self.unapply(
+            steps,
+            &mut |statement| executor(&statement.sql, &statement.values),
+            begin,
+            commit,
+            rollback,
+        )
+    }
+
+    /// The planned rollback, checked for reversibility before anything runs.
+    fn rollback_steps(
+        &mut self,
+        target: Option<&str>,
+    ) -> Result<Vec<(String, Rollback<'a>)>, MigrationError> {
         let to_unapply = self.plan_backward(target)?;
-        let mut unapplied = Vec::new();
-        let use_transactions = self.backend.supports_transactional_ddl();
+        let mut steps = Vec::new();
 
         for name in to_unapply {
             let migration = self
@@ -279,67 +592,146 @@ impl<'a, S: MigrationStateStore> Migrator<'a, S> {
                 .get(name)
                 .ok_or_else(|| MigrationError::NotFound(name.to_string()))?;
 
-            let sqls = migration
-                .backward_sql(self.backend)
-                .ok_or_else(|| MigrationError::NotReversible(name.to_string()))?;
+            if !migration.is_reversible() {
+                return Err(MigrationError::NotReversible(name.to_string()));
+            }
 
-            let should_wrap = use_transactions && migration.is_atomic();
+            steps.push((name.to_string(), Rollback::Migration(migration)));
+        }
 
-            if should_wrap {
-                begin().map_err(|e| MigrationError::ExecutionFailed {
-                    migration: name.to_string(),
-                    error: format!("begin transaction: {}", e),
-                    completed: unapplied.clone(),
-                })?;
-            }
+        Ok(steps)
+    }
 
-            let result = (|| {
-                for sql in sqls {
-                    executor(&sql)?;
-                }
-                Ok(())
-            })();
+    /// Roll back ghost migrations, those recorded as applied but no longer
+    /// in the registry, newest first, using the backward SQL the state
+    /// store kept when they were applied.
+    pub fn rollback_ghosts<F>(&mut self, mut executor: F) -> Result<Vec<String>, MigrationError>
+    where
+        F: FnMut(&str) -> Result<(), String>,
+    {
+        self.rollback_ghosts_with_transactions(
+            &mut executor,
+            &mut || Ok(()),
+            &mut || Ok(()),
+            &mut || Ok(()),
+        )
+    }
 
-            if let Err(e) = result {
-                if should_wrap {
-                    let _ = rollback();
-                }
-                return Err(MigrationError::ExecutionFailed {
-                    migration: name.to_string(),
-                    error: e,
-                    completed: unapplied,
-                });
-            }
+    /// Roll back ghost migrations with transaction support. Each ghost is
+    /// wrapped when it was atomic and the backend supports transactional DDL.
+    ///
+    /// Fails with `MigrationError::NotReversible` before running anything if
+    /// a ghost has no stored backward SQL.
+    pub fn rollback_ghosts_with_transactions<E, B, C, R>(
+        &mut self,
+        executor: &mut E,
+        begin: &mut B,
+        commit: &mut C,
+        rollback: &mut R,
+    ) -> Result<Vec<String>, MigrationError>
+    where
+        E: FnMut(&str) -> Result<(), String>,
+        B: FnMut() -> Result<(), String>,
+        C: FnMut() -> Result<(), String>,
+        R: FnMut() -> Result<(), String>,
+    {
+        let ghosts: Vec<String> = self
+            .applied()?
+            .into_iter()
+            .rev()
+            .filter(|name| self.registry.get(name).is_none())
+            .collect();
 
-            if should_wrap {
-                if let Err(e) = commit() {
-                    let _ = rollback();
-                    return Err(MigrationError::ExecutionFailed {
-                        migration: name.to_string(),
-                        error: format!("commit transaction: {}", e),
-                        completed: unapplied,
-                    });
-                }
-            }
+        let mut steps = Vec::new();
+        for name in ghosts {
+            let stored = self
+                .state
+                .stored_backward_sql(&name)
+                .map_err(state_error)?
+                .ok_or_else(|| MigrationError::NotReversible(name.clone()))?;
+            steps.push((name, Rollback::Stored(stored)));
+        }
+
+        self.unapply(
+            steps,
+            &mut |statement| executor(&statement.sql),
+            begin,
+            commit,
+            rollback,
+        )
+    }
+
+    /// Run each step's backward SQL and mark it unapplied.
+    fn unapply<B, C, R>(
+        &mut self,
+        steps: Vec<(String, Rollback<'_>)>,
+        executor: &mut dyn FnMut(&Statement) -> Result<(), String>,
+        begin: &mut B,
+        commit: &mut C,
+        rollback: &mut R,
+    ) -> Result<Vec<String>, MigrationError>
+    where
+        B: FnMut() -> Result<(), String>,
+        C: FnMut() -> Result<(), String>,
+        R: FnMut() -> Result<(), String>,
+    {
+        let mut unapplied = Vec::new();
+        let use_transactions = self.backend.supports_transactional_ddl();
 
-            self.state
-                .mark_unapplied(name)
-                .map_err(|e| MigrationError::ExecutionFailed {
-                    migration: name.to_string(),
-                    error: e,
-                    completed: unapplied.clone(),
-                })?;
+        for (name, step) in steps {
+            let wrap = use_transactions && step.is_atomic();
+            let backend = self.backend;
+            run_step(wrap, begin, commit, rollback, || match step {
+                Rollback::Migration(migration) => migration.unapply(backend, executor),
+                Rollback::Stored(stored) => stored
+                    .sql
+                    .into_iter()
+                    .try_for_each(|sql| executor(&Statement::new(sql))),
+            })
+            .and_then(|()| self.state.mark_unapplied(&name))
+            .map_err(|e| execution_failed(&name, e, &unapplied))?;
 
-            unapplied.push(name.to_string());
+            unapplied.push(name);
         }
 
         Ok(unapplied)
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationStatus {
+    Applied,
+    Pending,
+    /// Recorded as applied but missing from the registry.
+    Ghost,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusEntry {
+    pub name: String,
+    pub status: MigrationStatus,
+    /// Pending, but sorts before a migration that is already applied.
+    pub out_of_order: bool,
+}
+
+/// One line in the style of Django's `showmigrations`, e.g. `[X] 0001_initial`.
+impl std::fmt::Display for StatusEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.status {
+            MigrationStatus::Applied => write!(f, "[X] {}", self.name),
+            MigrationStatus::Pending if self.out_of_order => {
+                write!(f, "[ ] {} (out of order)", self.name)
+            }
+            MigrationStatus::Pending => write!(f, "[ ] {}", self.name),
+            MigrationStatus::Ghost => write!(f, "[?] {} (not in registry)", self.name),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct InMemoryState {
     applied: Vec<String>,
+    backward_sql: HashMap<String, StoredBackward>,
 }
 
 impl InMemoryState {
@@ -348,7 +740,10 @@ impl InMemoryState {
     }
 
     pub fn with_applied(applied: Vec<String>) -> Self {
-        Self { applied }
+        Self {
+            applied,
+            backward_sql: HashMap::new(),
+        }
     }
 }
 
@@ -366,17 +761,36 @@ impl MigrationStateStore for InMemoryState {
 
     fn mark_unapplied(&mut self, name: &str) -> Result<(), String> {
         self.applied.retain(|n| n != name);
+        self.backward_sql.remove(name);
         Ok(())
     }
+
+    fn mark_applied_with_backward(
+        &mut self,
+        name: &str,
+        backward: Option<&StoredBackward>,
+    ) -> Result<(), String> {
+        match backward {
+            Some(backward) => self.backward_sql.insert(name.to_string(), backward.clone()),
+            None => self.backward_sql.remove(name),
+        };
+        self.mark_applied(name)
+    }
+
+    fn stored_backward_sql(&mut self, name: &str) -> Result<Option<StoredBackward>, String> {
+        Ok(self.backward_sql.get(name).cloned())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::backend::Sqlite;
+    use crate::backend::{Postgres, Sqlite};
     use crate::field::{Field, FieldType};
     use crate::migration::Migration;
-    use crate::operation::{AddField, CreateTable, DropTable};
+    use crate::operation::{
+        AddEnumValue, AddField, Backfill, CreateTable, DropTable, RunCode, RunSql,
+    };
 
     fn setup_registry() -> MigrationRegistry {
         let mut registry = MigrationRegistry::new();
@@ -456,6 +870,30 @@ mod tests {
         assert!(sqls[1].1[0].contains("ADD COLUMN"));
     }
 
+    #[test]
+    fn unsupported_operation_fails_before_running() {
+        let status = Field::new("status", FieldType::enumeration("status", &["new"]));
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_add_value")
+                .operation(AddEnumValue::new("status", "done").column("orders", status)),
+        );
+
+        let mut migrator = Migrator::new(&registry, &Sqlite, InMemoryState::new());
+        let err = migrator.generate_forward_sql().unwrap_err();
+        assert!(err.to_string().contains("orders.status"));
+
+        let mut executed = Vec::new();
+        let err = migrator
+            .migrate_forward(|sql| {
+                executed.push(sql.to_string());
+                Ok(())
+            })
+            .unwrap_err();
+        assert!(matches!(err, MigrationError::ExecutionFailed { .. }));
+        assert!(executed.is_empty());
+    }
+
     #[test]
     fn generate_backward_sql() {
         let registry = setup_registry();
@@ -862,4 +1300,461 @@ mod tests {
         // The table creation has FK, but it's all in one statement
         assert!(sql[0].1[0].contains("CREATE TABLE"));
     }
+
+    fn setup_apps() -> MigrationRegistry {
+        let mut registry = MigrationRegistry::new();
+        registry.register_app(
+            "auth",
+            [Migration::new("0001_initial").operation(
+                CreateTable::new("users")
+                    .add_field(Field::new("id", FieldType::Serial).primary_key()),
+            )],
+        );
+        registry.register_app(
+            "billing",
+            [Migration::new("0001_initial")
+                .depends_on_latest("auth")
+                .operation(
+                    CreateTable::new("invoices")
+                        .add_field(Field::new("id", FieldType::Serial).primary_key()),
+                )],
+        );
+        registry.register_app(
+            "blog",
+            [Migration::new("0001_initial").operation(
+                CreateTable::new("posts")
+                    .add_field(Field::new("id", FieldType::Serial).primary_key()),
+            )],
+        );
+        registry
+    }
+
+    #[test]
+    fn plan_forward_for_app_includes_dependencies() {
+        let registry = setup_apps();
+        let mut migrator =
+            Migrator::new(&registry, &Sqlite, InMemoryState::new()).for_app("billing");
+
+        let plan = migrator.plan_forward().unwrap();
+        assert_eq!(plan, vec!["auth.0001_initial", "billing.0001_initial"]);
+    }
+
+    #[test]
+    fn plan_backward_for_app_includes_dependents() {
+        let registry = setup_apps();
+        let state = InMemoryState::with_applied(vec![
+            "auth.0001_initial".to_string(),
+            "billing.0001_initial".to_string(),
+            "blog.0001_initial".to_string(),
+        ]);
+        let mut migrator = Migrator::new(&registry, &Sqlite, state).for_app("auth");
+
+        let plan = migrator.plan_backward(None).unwrap();
+        assert_eq!(plan, vec!["billing.0001_initial", "auth.0001_initial"]);
+    }
+
+    #[test]
+    fn migrate_forward_for_app_records_qualified_names() {
+        let registry = setup_apps();
+        let mut migrator = Migrator::new(&registry, &Sqlite, InMemoryState::new()).for_app("blog");
+
+        let applied = migrator.migrate_forward(|_| Ok(())).unwrap();
+        assert_eq!(applied, vec!["blog.0001_initial"]);
+
+        let mut state = migrator.into_state();
+        assert_eq!(
+            state.applied_migrations().unwrap(),
+            vec!["blog.0001_initial"]
+        );
+    }
+
+    #[test]
+    fn plan_for_unknown_app() {
+        let registry = setup_apps();
+        let mut migrator = Migrator::new(&registry, &Sqlite, InMemoryState::new()).for_app("shop");
+
+        let result = migrator.plan_forward();
+        assert_eq!(result, Err(MigrationError::NotFound("shop".to_string())));
+    }
+
+    #[test]
+    fn status_reports_applied_and_pending() {
+        let registry = setup_registry();
+        let state = InMemoryState::with_applied(vec!["0001_create_users".to_string()]);
+        let mut migrator = Migrator::new(&registry, &Sqlite, state);
+
+        let status = migrator.status().unwrap();
+        assert_eq!(
+            status,
+            vec![
+                StatusEntry {
+                    name: "0001_create_users".to_string(),
+                    status: MigrationStatus::Applied,
+                    out_of_order: false,
+                },
+                StatusEntry {
+                    name: "0002_add_name".to_string(),
+                    status: MigrationStatus::Pending,
+                    out_of_order: false,
+                },
+            ]
+        );
+        assert_eq!(status[0].to_string(), "[X] 0001_create_users");
+        assert_eq!(status[1].to_string(), "[ ] 0002_add_name");
+    }
+
+    #[test]
+    fn status_reports_ghosts_and_gaps() {
+        let mut registry = setup_registry();
+        registry.register(Migration::new("0003_hotfix").depends_on(&["0001_create_users"]));
+        registry.register(Migration::new("0004_later").depends_on(&["0002_add_name"]));
+        let state = InMemoryState::with_applied(vec![
+            "0001_create_users".to_string(),
+            "0000_removed".to_string(),
+            "0002_add_name".to_string(),
+            "0004_later".to_string(),
+        ]);
+        let mut migrator = Migrator::new(&registry, &Sqlite, state);
+
+        let lines: Vec<String> = migrator
+            .status()
+            .unwrap()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "[X] 0001_create_users",
+                "[X] 0002_add_name",
+                "[ ] 0003_hotfix (out of order)",
+                "[X] 0004_later",
+                "[?] 0000_removed (not in registry)",
+            ]
+        );
+    }
+
+    fn setup_gap() -> (MigrationRegistry, InMemoryState) {
+        let mut registry = setup_registry();
+        registry.register(Migration::new("0003_hotfix").depends_on(&["0001_create_users"]));
+        registry.register(Migration::new("0004_later").depends_on(&["0002_add_name"]));
+        let state = InMemoryState::with_applied(vec![
+            "0001_create_users".to_string(),
+            "0002_add_name".to_string(),
+            "0004_later".to_string(),
+        ]);
+        (registry, state)
+    }
+
+    #[test]
+    fn out_of_order_allowed_by_default() {
+        let (registry, state) = setup_gap();
+        let mut migrator = Migrator::new(&registry, &Sqlite, state);
+
+        assert_eq!(migrator.plan_forward().unwrap(), vec!["0003_hotfix"]);
+    }
+
+    #[test]
+    fn out_of_order_warns() {
+        let (registry, state) = setup_gap();
+        let warnings = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = warnings.clone();
+        let mut migrator = Migrator::new(&registry, &Sqlite, state).out_of_order(
+            OutOfOrderPolicy::warn(move |migration, later| {
+                sink.lock()
+                    .unwrap()
+                    .push(format!("{} before {}", migration, later))
+            }),
+        );
+
+        assert_eq!(migrator.plan_forward().unwrap(), vec!["0003_hotfix"]);
+        assert_eq!(
+            *warnings.lock().unwrap(),
+            vec!["0003_hotfix before 0004_later"]
+        );
+    }
+
+    #[test]
+    fn migrator_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Migrator<'static, InMemoryState>>();
+    }
+
+    #[test]
+    fn out_of_order_errors() {
+        let (registry, state) = setup_gap();
+        let mut migrator =
+            Migrator::new(&registry, &Sqlite, state).out_of_order(OutOfOrderPolicy::Error);
+
+        let result = migrator.migrate_forward(|_| Ok(()));
+        assert_eq!(
+            result,
+            Err(MigrationError::OutOfOrder {
+                migration: "0003_hotfix".to_string(),
+                applied: "0004_later".to_string(),
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Migration 0003_hotfix is out of order: 0004_later is already applied"
+        );
+    }
+
+    #[test]
+    fn trailing_pending_migrations_are_in_order() {
+        let registry = setup_registry();
+        let state = InMemoryState::with_applied(vec!["0001_create_users".to_string()]);
+        let mut migrator =
+            Migrator::new(&registry, &Sqlite, state).out_of_order(OutOfOrderPolicy::Error);
+
+        assert_eq!(migrator.plan_forward().unwrap(), vec!["0002_add_name"]);
+    }
+
+    #[test]
+    fn rollback_ghosts_from_stored_sql() {
+        let registry = setup_registry();
+        let mut migrator = Migrator::new(&registry, &Sqlite, InMemoryState::new());
+        migrator.migrate_forward(|_| Ok(())).unwrap();
+        let state = migrator.into_state();
+
+        // The second migration was reverted out of the code base.
+        let mut reverted = MigrationRegistry::new();
+        reverted.register(
+            Migration::new("0001_create_users")
+                .operation(CreateTable::new("users").field("id", FieldType::Serial)),
+        );
+        let mut migrator = Migrator::new(&reverted, &Sqlite, state);
+
+        let mut executed = Vec::new();
+        let rolled_back = migrator
+            .rollback_ghosts(|sql| {
+                executed.push(sql.to_string());
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(rolled_back, vec!["0002_add_name"]);
+        assert_eq!(executed.len(), 1);
+        assert!(executed[0].contains("DROP COLUMN \"name\""));
+        assert_eq!(
+            migrator.state_mut().applied_migrations().unwrap(),
+            vec!["0001_create_users"]
+        );
+    }
+
+    #[test]
+    fn unresolvable_reverse_is_not_stored() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_sqlite_only_reverse").operation(
+                RunSql::portable()
+                    .for_backend("postgres", "SELECT 1")
+                    .for_backend_reversible("sqlite", "SELECT 2", "SELECT 3"),
+            ),
+        );
+
+        let mut executed = Vec::new();
+        let mut migrator = Migrator::new(&registry, &Postgres, InMemoryState::new());
+        migrator
+            .migrate_forward(|sql| {
+                executed.push(sql.to_string());
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(executed, vec!["SELECT 1"]);
+        assert_eq!(
+            migrator
+                .state_mut()
+                .stored_backward_sql("0001_sqlite_only_reverse")
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn rollback_ghosts_keeps_atomicity() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_users")
+                .operation(CreateTable::new("users").field("id", FieldType::Serial)),
+        );
+        registry.register(
+            Migration::new("0002_name")
+                .depends_on(&["0001_users"])
+                .atomic(false)
+                .operation(AddField::new("users", Field::new("name", FieldType::Text))),
+        );
+        let mut migrator = Migrator::new(&registry, &Postgres, InMemoryState::new());
+        migrator.migrate_forward(|_| Ok(())).unwrap();
+        let state = migrator.into_state();
+
+        let empty = MigrationRegistry::new();
+        let mut migrator = Migrator::new(&empty, &Postgres, state);
+        let log = std::cell::RefCell::new(Vec::new());
+        migrator
+            .rollback_ghosts_with_transactions(
+                &mut |sql| {
+                    log.borrow_mut().push(sql.to_string());
+                    Ok(())
+                },
+                &mut || {
+                    log.borrow_mut().push("BEGIN".to_string());
+                    Ok(())
+                },
+                &mut || {
+                    log.borrow_mut().push("COMMIT".to_string());
+                    Ok(())
+                },
+                &mut || Ok(()),
+            )
+            .unwrap();
+
+        let log = log.into_inner();
+        assert_eq!(log.len(), 4);
+        assert!(log[0].contains("DROP COLUMN \"name\""));
+        assert_eq!(log[1], "BEGIN");
+        assert!(log[2].starts_with("DROP TABLE"));
+        assert_eq!(log[3], "COMMIT");
+    }
+
+    #[test]
+    fn rollback_ghosts_without_stored_sql() {
+        let registry = setup_registry();
+        let state = InMemoryState::with_applied(vec![
+            "0001_create_users".to_string(),
+            "0002_add_name".to_string(),
+            "0003_removed".to_string(),
+        ]);
+        let mut migrator = Migrator::new(&registry, &Sqlite, state);
+
+        let result = migrator.rollback_ghosts(|_| panic!("nothing should run"));
+        assert_eq!(
+            result,
+            Err(MigrationError::NotReversible("0003_removed".to_string()))
+        );
+        assert_eq!(migrator.state_mut().applied_migrations().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn run_code_runs_in_order_inside_transaction() {
+        use std::sync::{Arc, Mutex};
+
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_rehash")
+                .operation(CreateTable::new("users").field("id", FieldType::Serial))
+                .operation(RunCode::reversible(
+                    |ctx| ctx.execute("UPDATE users SET hash = rehash(hash)"),
+                    |ctx| ctx.execute("UPDATE users SET hash = unhash(hash)"),
+                ))
+                .operation(AddField::new("users", Field::new("name", FieldType::Text))),
+        );
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let push = |entry: &str| log.lock().unwrap().push(entry.to_string());
+        let mut migrator = Migrator::new(&registry, &Postgres, InMemoryState::new());
+        migrator
+            .migrate_forward_with_transactions(
+                &mut |sql| {
+                    push(sql);
+                    Ok(())
+                },
+                &mut || {
+                    push("BEGIN");
+                    Ok(())
+                },
+                &mut || {
+                    push("COMMIT");
+                    Ok(())
+                },
+                &mut || Ok(()),
+            )
+            .unwrap();
+        assert_eq!(
+            migrator
+                .state_mut()
+                .stored_backward_sql("0001_rehash")
+                .unwrap(),
+            None
+        );
+
+        migrator
+            .migrate_backward(None, |sql| {
+                push(sql);
+                Ok(())
+            })
+            .unwrap();
+
+        let log = log.lock().unwrap();
+        assert_eq!(log[0], "BEGIN");
+        assert!(log[1].starts_with("CREATE TABLE"));
+        assert_eq!(log[2], "UPDATE users SET hash = rehash(hash)");
+        assert!(log[3].contains("ADD COLUMN"));
+        assert_eq!(log[4], "COMMIT");
+        assert!(log[5].contains("DROP COLUMN"));
+        assert_eq!(log[6], "UPDATE users SET hash = unhash(hash)");
+        assert!(log[7].starts_with("DROP TABLE"));
+    }
+
+    #[test]
+    fn run_code_failure_rolls_back() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_fix").operation(RunCode::new(|_| Err("bad row".to_string()))),
+        );
+
+        let mut rolled_back = false;
+        let mut migrator = Migrator::new(&registry, &Postgres, InMemoryState::new());
+        let result = migrator.migrate_forward_with_transactions(
+            &mut |_| Ok(()),
+            &mut || Ok(()),
+            &mut || Ok(()),
+            &mut || {
+                rolled_back = true;
+                Ok(())
+            },
+        );
+
+        assert_eq!(
+            result,
+            Err(MigrationError::ExecutionFailed {
+                migration: "0001_fix".to_string(),
+                error: "bad row".to_string(),
+                completed: vec![],
+            })
+        );
+        assert!(rolled_back);
+        assert!(migrator
+            .state_mut()
+            .applied_migrations()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn backfill_batches_commit_separately() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_backfill")
+                .operation(Backfill::new("users", "active = TRUE", 1..=5000).batch_size(2000)),
+        );
+
+        let mut executed = Vec::new();
+        let mut migrator = Migrator::new(&registry, &Postgres, InMemoryState::new());
+        migrator
+            .migrate_forward_with_transactions(
+                &mut |sql| {
+                    executed.push(sql.to_string());
+                    Ok(())
+                },
+                &mut || Err("non-atomic migrations must not open a transaction".to_string()),
+                &mut || Ok(()),
+                &mut || Ok(()),
+            )
+            .unwrap();
+
+        assert_eq!(executed.len(), 3);
+        assert!(executed[2].ends_with("BETWEEN 4001 AND 5000"));
+    }
 }
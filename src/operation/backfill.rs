@@ -0,0 +1,329 @@
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+use crate::backend::Backend;
+#[cfg(feature = "serde")]
+use crate::operation::SerializedOperation;
+use crate::operation::{Operation, RunContext, Statement, Touch};
+
+/// Update a large table in key-range batches, e.g. to fill a new column,
+/// instead of one long statement that holds locks on every row.
+///
+/// Each batch is its own `UPDATE ... WHERE key BETWEEN lo AND hi`. The
+/// operation is non-atomic, so a migration containing it runs outside a
+/// transaction and every batch commits on its own.
+///
+/// `forward` and `backward` render every batch, so previews show the real
+/// SQL. With a pause the batches run as code instead, to sleep between them.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Backfill {
+    pub table: String,
+    /// Raw `SET` assignments, e.g. `email_lower = LOWER(email)`.
+    pub assignments: String,
+    pub reverse_assignments: Option<String>,
+    pub key: String,
+    pub filter: Option<String>,
+    /// Inclusive key range to cover.
+    pub range: (i64, i64),
+    pub batch_size: u64,
+    /// Serialized as `pause_ms`, in milliseconds.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename = "pause_ms",
+            serialize_with = "serialize_millis",
+            deserialize_with = "deserialize_millis"
+        )
+    )]
+    pub pause: Duration,
+}
+
+impl Backfill {
+    /// Backfill keys `range`, e.g. `1..=max_id`. The executor can't query,
+    /// so when the bounds are only known at runtime, build the `Backfill`
+    /// inside a `RunCode` and call [`Backfill::run`].
+    pub fn new(
+        table: impl Into<String>,
+        assignments: impl Into<String>,
+        range: RangeInclusive<i64>,
+    ) -> Self {
+        Self {
+            table: table.into(),
+            assignments: assignments.into(),
+            reverse_assignments: None,
+            key: "id".to_string(),
+            filter: None,
+            range: range.into_inner(),
+            batch_size: 1000,
+            pause: Duration::ZERO,
+        }
+    }
+
+    /// The integer key column batches are ranged over. Defaults to `id`.
+    pub fn key(mut self, column: impl Into<String>) -> Self {
+        self.key = column.into();
+        self
+    }
+
+    pub fn batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Sleep between batches to leave room for other traffic.
+    pub fn pause(mut self, pause: Duration) -> Self {
+        self.pause = pause;
+        self
+    }
+
+    /// Only update rows matching this SQL condition.
+    pub fn filter(mut self, condition: impl Into<String>) -> Self {
+        self.filter = Some(condition.into());
+        self
+    }
+
+    /// Assignments that undo the backfill, run in batches the same way.
+    pub fn with_reverse(mut self, assignments: impl Into<String>) -> Self {
+        self.reverse_assignments = Some(assignments.into());
+        self
+    }
+
+    /// The batch statements for `assignments`, in key order.
+    pub fn statements(&self, backend: &dyn Backend, assignments: &str) -> Vec<String> {
+        let (start, end) = self.range;
+        let step = self.batch_size.clamp(1, i64::MAX as u64) as i64;
+        let filter = self
+            .filter
+            .as_ref()
+            .map(|condition| format!(" AND ({})", condition))
+            .unwrap_or_default();
+
+        let mut statements = Vec::new();
+        let mut low = start;
+        while low <= end {
+            let high = low.saturating_add(step - 1).min(end);
+            statements.push(format!(
+                "UPDATE {} SET {} WHERE {} BETWEEN {} AND {}{}",
+                backend.quote_identifier(&self.table),
+                assignments,
+                backend.quote_identifier(&self.key),
+                low,
+                high,
+                filter
+            ));
+            if high == end {
+                break;
+            }
+            low = high + 1;
+        }
+        statements
+    }
+
+    /// Run the forward batches through `context`, pausing between them.
+    pub fn run(&self, context: &mut RunContext<'_>) -> Result<(), String> {
+        self.run_batches(context, &self.assignments)
+    }
+
+    fn run_batches(&self, context: &mut RunContext<'_>, assignments: &str) -> Result<(), String> {
+        let statements = self.statements(context.backend(), assignments);
+        for (i, sql) in statements.iter().enumerate() {
+            if i > 0 && !self.pause.is_zero() {
+                std::thread::sleep(self.pause);
+            }
+            context.execute(sql)?;
+        }
+        Ok(())
+    }
+}
+
+impl Operation for Backfill {
+    fn forward(&self, backend: &dyn Backend) -> Vec<String> {
+        self.statements(backend, &self.assignments)
+    }
+
+    fn backward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        self.reverse_assignments
+            .as_ref()
+            .map(|assignments| self.statements(backend, assignments))
+    }
+
+    fn forward_statements(&self, backend: &dyn Backend) -> Vec<Statement> {
+        if self.runs_code() {
+            return Vec::new();
+        }
+        self.forward(backend)
+            .into_iter()
+            .map(Statement::new)
+            .collect()
+    }
+
+    fn backward_statements(&self, backend: &dyn Backend) -> Option<Vec<Statement>> {
+        if self.runs_code() {
+            return self.reverse_assignments.as_ref().map(|_| Vec::new());
+        }
+        self.backward(backend)
+            .map(|sqls| sqls.into_iter().map(Statement::new).collect())
+    }
+
+    fn describe(&self) -> String {
+        format!("Backfill {}", self.table)
+    }
+
+    fn is_reversible(&self) -> bool {
+        self.reverse_assignments.is_some()
+    }
+
+    fn is_atomic(&self) -> bool {
+        false
+    }
+
+    fn touches(&self) -> Vec<Touch> {
+        vec![Touch::reference(&self.table, None)]
+    }
+
+    fn run_forward(&self, context: &mut RunContext<'_>) -> Result<(), String> {
+        if !self.runs_code() {
+            return Ok(());
+        }
+        self.run(context)
+    }
+
+    fn run_backward(&self, context: &mut RunContext<'_>) -> Result<(), String> {
+        match self.reverse_assignments {
+            Some(ref assignments) if self.runs_code() => self.run_batches(context, assignments),
+            _ => Ok(()),
+        }
+    }
+
+    /// Only a pause needs code; without one the batches are plain SQL.
+    fn runs_code(&self) -> bool {
+        !self.pause.is_zero()
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn serialize_millis<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_u64(duration.as_millis().try_into().unwrap_or(u64::MAX))
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_millis<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    <u64 as serde::Deserialize>::deserialize(deserializer).map(Duration::from_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{MySql, Postgres};
+
+    #[test]
+    fn batches_cover_range() {
+        let op = Backfill::new("users", "email_lower = LOWER(email)", 1..=25)
+            .batch_size(10)
+            .filter("email_lower IS NULL");
+
+        assert_eq!(
+            op.statements(&Postgres, &op.assignments),
+            vec![
+                "UPDATE \"users\" SET email_lower = LOWER(email) WHERE \"id\" BETWEEN 1 AND 10 AND (email_lower IS NULL)",
+                "UPDATE \"users\" SET email_lower = LOWER(email) WHERE \"id\" BETWEEN 11 AND 20 AND (email_lower IS NULL)",
+                "UPDATE \"users\" SET email_lower = LOWER(email) WHERE \"id\" BETWEEN 21 AND 25 AND (email_lower IS NULL)",
+            ]
+        );
+    }
+
+    #[test]
+    fn custom_key_and_quoting() {
+        let op = Backfill::new("events", "processed = 1", i64::MAX - 1..=i64::MAX)
+            .key("event_id")
+            .batch_size(100);
+
+        assert_eq!(
+            op.statements(&MySql, &op.assignments),
+            vec![format!(
+                "UPDATE `events` SET processed = 1 WHERE `event_id` BETWEEN {} AND {}",
+                i64::MAX - 1,
+                i64::MAX
+            )]
+        );
+    }
+
+    #[test]
+    fn previews_render_batches() {
+        let op = Backfill::new("users", "score = 0", 1..=3)
+            .batch_size(2)
+            .with_reverse("score = NULL");
+        assert!(!op.runs_code());
+        assert_eq!(op.forward(&Postgres).len(), 2);
+        assert_eq!(
+            op.backward_statements(&Postgres).unwrap()[1].sql,
+            "UPDATE \"users\" SET score = NULL WHERE \"id\" BETWEEN 3 AND 3"
+        );
+
+        let mut executed = Vec::new();
+        let mut executor = |statement: &Statement| {
+            executed.push(statement.sql.clone());
+            Ok(())
+        };
+        op.run_forward(&mut RunContext::new(&Postgres, &mut executor))
+            .unwrap();
+        assert!(executed.is_empty());
+    }
+
+    #[test]
+    fn runs_batches_and_reverse() {
+        let op = Backfill::new("users", "score = 0", 1..=3)
+            .batch_size(2)
+            .pause(Duration::from_millis(1))
+            .with_reverse("score = NULL");
+        assert!(!op.is_atomic());
+        assert!(op.is_reversible());
+        assert!(op.runs_code());
+        assert!(op.forward_statements(&Postgres).is_empty());
+        assert_eq!(op.forward(&Postgres).len(), 2);
+
+        let mut executed = Vec::new();
+        let mut executor = |statement: &Statement| {
+            executed.push(statement.sql.clone());
+            Ok(())
+        };
+        let mut context = RunContext::new(&Postgres, &mut executor);
+        op.run_forward(&mut context).unwrap();
+        op.run_backward(&mut context).unwrap();
+
+        assert_eq!(executed.len(), 4);
+        assert!(executed[1].ends_with("BETWEEN 3 AND 3"));
+        assert!(executed[2].starts_with("UPDATE \"users\" SET score = NULL"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn pause_serializes_as_milliseconds() {
+        let op = Backfill::new("users", "score = 0", 1..=3).pause(Duration::from_millis(50));
+
+        let json = serde_json::to_value(&op).unwrap();
+        assert_eq!(json["pause_ms"], 50);
+        let restored: Backfill = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.pause, op.pause);
+        assert_eq!(restored.range, (1, 3));
+    }
+
+    #[test]
+    fn empty_table_has_no_batches() {
+        let max_id = 0;
+        let op = Backfill::new("users", "score = 0", 1..=max_id);
+        assert!(op.statements(&Postgres, &op.assignments).is_empty());
+    }
+}
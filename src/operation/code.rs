@@ -0,0 +1,223 @@
+use crate::backend::Backend;
+use crate::operation::{Operation, Statement, Value};
+
+/// The handle `RunCode` closures receive: the migration's backend and the
+/// executor the `Migrator` was given. Statements run through it share the
+/// migration's transaction.
+pub struct RunContext<'e> {
+    backend: &'e dyn Backend,
+    executor: &'e mut dyn FnMut(&Statement) -> Result<(), String>,
+}
+
+impl<'e> RunContext<'e> {
+    pub fn new(
+        backend: &'e dyn Backend,
+        executor: &'e mut dyn FnMut(&Statement) -> Result<(), String>,
+    ) -> Self {
+        Self { backend, executor }
+    }
+
+    pub fn backend(&self) -> &dyn Backend {
+        self.backend
+    }
+
+    pub fn execute(&mut self, sql: &str) -> Result<(), String> {
+        (self.executor)(&Statement::new(sql))
+    }
+
+    /// Execute SQL with `$1`-style placeholders bound to `values`, see
+    /// [`Statement::bind`].
+    pub fn execute_with(&mut self, sql: &str, values: &[Value]) -> Result<(), String> {
+        let statement = Statement::bind(self.backend, sql, values);
+        (self.executor)(&statement)
+    }
+}
+
+type CodeFn = Box<dyn Fn(&mut RunContext<'_>) -> Result<(), String> + Send + Sync>;
+
+/// Run Rust code as part of a migration, for data changes that need
+/// application logic. It generates no SQL of its own, so SQL previews and
+/// exports leave it out.
+pub struct RunCode {
+    forward: CodeFn,
+    backward: Option<CodeFn>,
+    description: String,
+    elidable: bool,
+}
+
+impl RunCode {
+    /// Create an irreversible RunCode operation.
+    pub fn new<F>(forward: F) -> Self
+    where
+        F: Fn(&mut RunContext<'_>) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Self {
+            forward: Box::new(forward),
+            backward: None,
+            description: "Run custom code".to_string(),
+            elidable: false,
+        }
+    }
+
+    /// Create a reversible RunCode operation.
+    pub fn reversible<F, B>(forward: F, backward: B) -> Self
+    where
+        F: Fn(&mut RunContext<'_>) -> Result<(), String> + Send + Sync + 'static,
+        B: Fn(&mut RunContext<'_>) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Self::new(forward).with_reverse(backward)
+    }
+
+    /// Add the code that undoes this operation.
+    pub fn with_reverse<B>(mut self, backward: B) -> Self
+    where
+        B: Fn(&mut RunContext<'_>) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.backward = Some(Box::new(backward));
+        self
+    }
+
+    /// Make the operation reversible without doing anything on rollback.
+    pub fn with_noop_reverse(self) -> Self {
+        self.with_reverse(|_| Ok(()))
+    }
+
+    /// Mark the operation as only needed against existing data, so squashing
+    /// may drop it: a database built from the squashed migration starts empty.
+    pub fn elidable(mut self) -> Self {
+        self.elidable = true;
+        self
+    }
+
+    /// Set a description for this operation.
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+}
+
+impl std::fmt::Debug for RunCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RunCode")
+            .field("description", &self.description)
+            .field("reversible", &self.backward.is_some())
+            .field("elidable", &self.elidable)
+            .finish()
+    }
+}
+
+impl Operation for RunCode {
+    fn forward(&self, _backend: &dyn Backend) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn backward(&self, _backend: &dyn Backend) -> Option<Vec<String>> {
+        self.backward.as_ref().map(|_| Vec::new())
+    }
+
+    fn describe(&self) -> String {
+        self.description.clone()
+    }
+
+    fn is_reversible(&self) -> bool {
+        self.backward.is_some()
+    }
+
+    fn run_forward(&self, context: &mut RunContext<'_>) -> Result<(), String> {
+        (self.forward)(context)
+    }
+
+    fn run_backward(&self, context: &mut RunContext<'_>) -> Result<(), String> {
+        match self.backward {
+            Some(ref backward) => backward(context),
+            None => Ok(()),
+        }
+    }
+
+    fn runs_code(&self) -> bool {
+        true
+    }
+
+    fn is_elidable(&self) -> bool {
+        self.elidable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Postgres, Sqlite};
+
+    #[test]
+    fn run_code_executes_through_context() {
+        let op = RunCode::new(|ctx| {
+            let sql = format!("UPDATE users SET backend = '{}'", ctx.backend().name());
+            ctx.execute(&sql)
+        });
+
+        let mut executed = Vec::new();
+        let mut executor = |statement: &Statement| {
+            executed.push(statement.sql.clone());
+            Ok(())
+        };
+        op.run_forward(&mut RunContext::new(&Postgres, &mut executor))
+            .unwrap();
+
+        assert_eq!(executed, vec!["UPDATE users SET backend = 'postgres'"]);
+        assert!(op.forward(&Postgres).is_empty());
+        assert!(op.runs_code());
+    }
+
+    #[test]
+    fn run_context_binds_values() {
+        let mut executed = Vec::new();
+        let mut executor = |statement: &Statement| {
+            executed.push(statement.clone());
+            Ok(())
+        };
+        RunContext::new(&Postgres, &mut executor)
+            .execute_with(
+                "UPDATE users SET hash = $2 WHERE id = $1",
+                &[7.into(), "x".into()],
+            )
+            .unwrap();
+
+        assert_eq!(
+            executed,
+            vec![Statement {
+                sql: "UPDATE users SET hash = $1 WHERE id = $2".to_string(),
+                values: vec!["x".into(), 7.into()],
+            }]
+        );
+    }
+
+    #[test]
+    fn run_code_reversibility() {
+        let op = RunCode::new(|_| Ok(()));
+        assert!(!op.is_reversible());
+        assert!(op.backward(&Sqlite).is_none());
+
+        let op = op.with_noop_reverse();
+        assert!(op.is_reversible());
+        assert_eq!(op.backward(&Sqlite), Some(vec![]));
+    }
+
+    #[test]
+    fn run_code_errors_propagate() {
+        let op = RunCode::reversible(|_| Ok(()), |_| Err("cannot restore".to_string()));
+        let mut executor = |_: &Statement| Ok(());
+
+        let result = op.run_backward(&mut RunContext::new(&Sqlite, &mut executor));
+        assert_eq!(result, Err("cannot restore".to_string()));
+    }
+
+    #[test]
+    fn run_code_elidable_and_description() {
+        let op = RunCode::new(|_| Ok(()));
+        assert!(!op.is_elidable());
+
+        let op = op.elidable().with_description("Rehash passwords");
+        assert!(op.is_elidable());
+        assert_eq!(Operation::describe(&op), "Rehash passwords");
+    }
+}
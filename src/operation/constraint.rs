@@ -1,8 +1,13 @@
 use crate::backend::Backend;
 use crate::field::ReferentialAction;
-use crate::operation::Operation;
+#[cfg(feature = "serde")]
+use crate::operation::SerializedOperation;
+use crate::operation::{Operation, Touch};
+use crate::schema::SchemaState;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "snake_case"))]
 pub enum Constraint {
     Check {
         name: String,
@@ -17,7 +22,9 @@ pub enum Constraint {
         columns: Vec<String>,
         ref_table: String,
         ref_columns: Vec<String>,
+        #[cfg_attr(feature = "serde", serde(default))]
         on_delete: ReferentialAction,
+        #[cfg_attr(feature = "serde", serde(default))]
         on_update: ReferentialAction,
     },
 }
@@ -83,6 +90,7 @@ impl Constraint {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddConstraint {
     pub table: String,
     pub constraint: Constraint,
@@ -115,9 +123,27 @@ impl Operation for AddConstraint {
             self.table
         )
     }
+
+    fn touches(&self) -> Vec<Touch> {
+        constraint_touches(&self.table, Some(&self.constraint))
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        if let Some(table) = state.table_mut(&self.table) {
+            let name = self.constraint.name();
+            table.constraints.retain(|c| c.name() != name);
+            table.constraints.push(self.constraint.clone());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RemoveConstraint {
     pub table: String,
     pub name: String,
@@ -154,9 +180,50 @@ impl Operation for RemoveConstraint {
         format!("Remove constraint {} from {}", self.name, self.table)
     }
 
+    fn touches(&self) -> Vec<Touch> {
+        constraint_touches(&self.table, self.constraint.as_ref())
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        if let Some(table) = state.table_mut(&self.table) {
+            table.constraints.retain(|c| c.name() != self.name);
+        }
+    }
+
     fn is_reversible(&self) -> bool {
         self.constraint.is_some()
     }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
+}
+
+/// Constrained columns are altered touches; a foreign key also references its
+/// target. Check constraints and unknown definitions only touch the table.
+fn constraint_touches(table: &str, constraint: Option<&Constraint>) -> Vec<Touch> {
+    match constraint {
+        Some(Constraint::Unique { columns, .. }) => columns
+            .iter()
+            .map(|column| Touch::alter(table, Some(column)))
+            .collect(),
+        Some(Constraint::ForeignKey {
+            columns,
+            ref_table,
+            ref_columns,
+            ..
+        }) => columns
+            .iter()
+            .map(|column| Touch::alter(table, Some(column)))
+            .chain(
+                ref_columns
+                    .iter()
+                    .map(|column| Touch::reference(ref_table, Some(column))),
+            )
+            .collect(),
+        Some(Constraint::Check { .. }) | None => vec![Touch::alter(table, None)],
+    }
 }
 
 #[cfg(test)]
@@ -285,4 +352,27 @@ mod tests {
         let backward = op.backward(&Sqlite).unwrap();
         assert!(backward[0].contains("CHECK"));
     }
+
+    #[test]
+    fn constraint_touches() {
+        let op = AddConstraint::new(
+            "posts",
+            Constraint::foreign_key(
+                "fk_posts_author",
+                vec!["author_id".to_string()],
+                "users",
+                vec!["id".to_string()],
+            ),
+        );
+        assert_eq!(
+            op.touches(),
+            vec![
+                Touch::alter("posts", Some("author_id")),
+                Touch::reference("users", Some("id")),
+            ]
+        );
+
+        let op = RemoveConstraint::new("users", "chk_age");
+        assert_eq!(op.touches(), vec![Touch::alter("users", None)]);
+    }
 }
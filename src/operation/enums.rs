@@ -0,0 +1,620 @@
+use crate::backend::{string_list, Backend, FieldChanges};
+use crate::field::{DefaultValue, Field, FieldType};
+#[cfg(feature = "serde")]
+use crate::operation::SerializedOperation;
+use crate::operation::{Operation, Touch};
+use crate::schema::SchemaState;
+
+/// Create a named enum type for `FieldType::Enum` columns to use.
+///
+/// Only PostgreSQL has enum types; elsewhere the variants are spelled out in
+/// each column, so this generates no SQL there.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CreateEnum {
+    pub name: String,
+    pub variants: Vec<String>,
+}
+
+impl CreateEnum {
+    pub fn new(name: impl Into<String>, variants: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            variants: variants.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+}
+
+fn create_enum_sql(backend: &dyn Backend, name: &str, variants: &[String]) -> Vec<String> {
+    if !backend.supports_enum_types() {
+        return Vec::new();
+    }
+    vec![format!(
+        "CREATE TYPE {} AS ENUM ({})",
+        backend.quote_identifier(name),
+        string_list(backend, variants)
+    )]
+}
+
+fn drop_enum_sql(backend: &dyn Backend, name: &str) -> Vec<String> {
+    if !backend.supports_enum_types() {
+        return Vec::new();
+    }
+    vec![format!("DROP TYPE {}", backend.quote_identifier(name))]
+}
+
+impl Operation for CreateEnum {
+    fn forward(&self, backend: &dyn Backend) -> Vec<String> {
+        create_enum_sql(backend, &self.name, &self.variants)
+    }
+
+    fn backward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        Some(drop_enum_sql(backend, &self.name))
+    }
+
+    fn describe(&self) -> String {
+        format!("Create enum {}", self.name)
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
+}
+
+/// Drop a named enum type. Provide the variants to make it reversible.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DropEnum {
+    pub name: String,
+    pub variants: Option<Vec<String>>,
+}
+
+impl DropEnum {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            variants: None,
+        }
+    }
+
+    pub fn with_variants(mut self, variants: &[&str]) -> Self {
+        self.variants = Some(variants.iter().map(|v| v.to_string()).collect());
+        self
+    }
+}
+
+impl Operation for DropEnum {
+    fn forward(&self, backend: &dyn Backend) -> Vec<String> {
+        drop_enum_sql(backend, &self.name)
+    }
+
+    fn backward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        self.variants
+            .as_ref()
+            .map(|variants| create_enum_sql(backend, &self.name, variants))
+    }
+
+    fn describe(&self) -> String {
+        format!("Drop enum {}", self.name)
+    }
+
+    fn is_reversible(&self) -> bool {
+        self.variants.is_some()
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
+}
+
+/// Where `AddEnumValue` puts the new value. Without one it goes last.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum EnumValuePosition {
+    Before(String),
+    After(String),
+}
+
+/// A column typed as an enum, with its current definition. Backends without
+/// enum types redefine these columns when the variants change.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EnumColumn {
+    pub table: String,
+    pub field: Field,
+}
+
+impl EnumColumn {
+    fn variants(&self) -> Vec<String> {
+        match self.field.field_type {
+            FieldType::Enum { ref variants, .. } => variants.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The column with a default of `from` changed to `to`, so it stays a
+    /// valid variant after a rename.
+    fn rename_default(&self, backend: &dyn Backend, from: &str, to: &str) -> EnumColumn {
+        let mut column = self.clone();
+        column.field.default = match self.field.default {
+            Some(DefaultValue::String(ref value)) if value == from => {
+                Some(DefaultValue::string(to))
+            }
+            Some(DefaultValue::Raw(ref sql)) if *sql == backend.string_literal(from) => {
+                Some(DefaultValue::raw(backend.string_literal(to)))
+            }
+            ref default => default.clone(),
+        };
+        column
+    }
+
+    /// The column redefined with `variants`, keeping nullability and default.
+    fn redefine_sql(
+        &self,
+        backend: &dyn Backend,
+        name: &str,
+        variants: Vec<String>,
+    ) -> Vec<String> {
+        let changes = FieldChanges {
+            field_type: Some(FieldType::Enum {
+                name: name.to_string(),
+                variants,
+            }),
+            nullable: Some(self.field.nullable),
+            default: Some(self.field.default.clone()),
+        };
+        backend.alter_field_sql(&self.table, &self.field.name, &changes)
+    }
+}
+
+/// Without enum types the columns must be listed, or nothing would change.
+/// Without `ALTER COLUMN` either (SQLite), the variants live in a CHECK
+/// constraint that only a table rebuild can change.
+fn check_enum_columns(
+    backend: &dyn Backend,
+    name: &str,
+    columns: &[EnumColumn],
+) -> Result<(), String> {
+    match columns.first() {
+        None if !backend.supports_enum_types() => Err(format!(
+            "enum {} has no columns to change on {}; list them with .column()",
+            name,
+            backend.name()
+        )),
+        Some(column) if !backend.supports_enum_types() && !backend.supports_alter_column() => {
+            Err(format!(
+                "enum column {}.{} can't be changed in place on {}; rebuild the table with RunSql",
+                column.table,
+                column.field.name,
+                backend.name()
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn enum_column_touches(columns: &[EnumColumn]) -> Vec<Touch> {
+    columns
+        .iter()
+        .map(|c| Touch::alter(&c.table, Some(&c.field.name)))
+        .collect()
+}
+
+/// Add a value to an enum.
+///
+/// On PostgreSQL this is `ALTER TYPE ... ADD VALUE`, which can't be undone
+/// and can't be used in the transaction that adds it, so the operation is
+/// irreversible and non-atomic on every backend. Elsewhere each column given
+/// with [`AddEnumValue::column`] is redefined with the new variants, and
+/// `backward` restores the old ones; SQLite can't redefine columns, so there
+/// the operation fails its [`Operation::check`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AddEnumValue {
+    pub name: String,
+    pub value: String,
+    pub position: Option<EnumValuePosition>,
+    pub columns: Vec<EnumColumn>,
+}
+
+impl AddEnumValue {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            position: None,
+            columns: Vec::new(),
+        }
+    }
+
+    pub fn before(mut self, variant: impl Into<String>) -> Self {
+        self.position = Some(EnumValuePosition::Before(variant.into()));
+        self
+    }
+
+    pub fn after(mut self, variant: impl Into<String>) -> Self {
+        self.position = Some(EnumValuePosition::After(variant.into()));
+        self
+    }
+
+    /// A column using the enum, with its definition before this operation.
+    pub fn column(mut self, table: impl Into<String>, field: Field) -> Self {
+        self.columns.push(EnumColumn {
+            table: table.into(),
+            field,
+        });
+        self
+    }
+
+    fn add_to(&self, variants: &mut Vec<String>) {
+        let anchor = |variant: &String| variants.iter().position(|v| v == variant);
+        let index = match self.position {
+            Some(EnumValuePosition::Before(ref variant)) => anchor(variant),
+            Some(EnumValuePosition::After(ref variant)) => anchor(variant).map(|i| i + 1),
+            None => None,
+        };
+        variants.insert(index.unwrap_or(variants.len()), self.value.clone());
+    }
+}
+
+impl Operation for AddEnumValue {
+    fn forward(&self, backend: &dyn Backend) -> Vec<String> {
+        if backend.supports_enum_types() {
+            let position = match self.position {
+                Some(EnumValuePosition::Before(ref variant)) => {
+                    format!(" BEFORE {}", backend.string_literal(variant))
+                }
+                Some(EnumValuePosition::After(ref variant)) => {
+                    format!(" AFTER {}", backend.string_literal(variant))
+                }
+                None => String::new(),
+            };
+            return vec![format!(
+                "ALTER TYPE {} ADD VALUE {}{}",
+                backend.quote_identifier(&self.name),
+                backend.string_literal(&self.value),
+                position
+            )];
+        }
+        if check_enum_columns(backend, &self.name, &self.columns).is_err() {
+            return Vec::new();
+        }
+        self.columns
+            .iter()
+            .flat_map(|column| {
+                let mut variants = column.variants();
+                self.add_to(&mut variants);
+                column.redefine_sql(backend, &self.name, variants)
+            })
+            .collect()
+    }
+
+    /// Redefines the columns with their old variants, except on PostgreSQL,
+    /// which can't drop an enum value.
+    fn backward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        if backend.supports_enum_types() {
+            return None;
+        }
+        if check_enum_columns(backend, &self.name, &self.columns).is_err() {
+            return Some(Vec::new());
+        }
+        Some(
+            self.columns
+                .iter()
+                .flat_map(|column| column.redefine_sql(backend, &self.name, column.variants()))
+                .collect(),
+        )
+    }
+
+    fn describe(&self) -> String {
+        format!("Add value {} to enum {}", self.value, self.name)
+    }
+
+    // Reversibility and atomicity are properties of the migration, not of
+    // where it runs, so they follow PostgreSQL. Elsewhere nothing is lost by
+    // running outside a transaction: MySQL commits DDL implicitly, and
+    // SQLite refuses the operation (see `check`).
+    fn is_reversible(&self) -> bool {
+        false
+    }
+
+    fn is_atomic(&self) -> bool {
+        false
+    }
+
+    fn check(&self, backend: &dyn Backend) -> Result<(), String> {
+        check_enum_columns(backend, &self.name, &self.columns)
+    }
+
+    fn touches(&self) -> Vec<Touch> {
+        enum_column_touches(&self.columns)
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        state.update_enum(&self.name, |variants| self.add_to(variants));
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
+}
+
+/// Rename an enum value, keeping rows that hold it.
+///
+/// PostgreSQL renames the value in place. Elsewhere each column given with
+/// [`RenameEnumValue::column`] is widened to allow both names, its rows are
+/// updated, and the old name is dropped. Like [`AddEnumValue`], this fails
+/// its check on SQLite when columns are given.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RenameEnumValue {
+    pub name: String,
+    pub old_value: String,
+    pub new_value: String,
+    pub columns: Vec<EnumColumn>,
+}
+
+impl RenameEnumValue {
+    pub fn new(
+        name: impl Into<String>,
+        old_value: impl Into<String>,
+        new_value: impl Into<String>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            old_value: old_value.into(),
+            new_value: new_value.into(),
+            columns: Vec::new(),
+        }
+    }
+
+    /// A column using the enum, with its definition before this operation.
+    pub fn column(mut self, table: impl Into<String>, field: Field) -> Self {
+        self.columns.push(EnumColumn {
+            table: table.into(),
+            field,
+        });
+        self
+    }
+
+    fn rename_sql(&self, backend: &dyn Backend, from: &str, to: &str) -> Vec<String> {
+        if backend.supports_enum_types() {
+            return vec![format!(
+                "ALTER TYPE {} RENAME VALUE {} TO {}",
+                backend.quote_identifier(&self.name),
+                backend.string_literal(from),
+                backend.string_literal(to)
+            )];
+        }
+
+        let mut sql = Vec::new();
+        if check_enum_columns(backend, &self.name, &self.columns).is_err() {
+            return sql;
+        }
+        for column in &self.columns {
+            let before = column.variants();
+            let before = if before.iter().any(|v| v == from) {
+                before
+            } else {
+                rename_variant(&before, to, from)
+            };
+            let mut both = before.clone();
+            both.push(to.to_string());
+
+            sql.extend(column.redefine_sql(backend, &self.name, both));
+            let field = backend.quote_identifier(&column.field.name);
+            sql.push(format!(
+                "UPDATE {} SET {} = {} WHERE {} = {}",
+                backend.quote_identifier(&column.table),
+                field,
+                backend.string_literal(to),
+                field,
+                backend.string_literal(from)
+            ));
+            sql.extend(column.rename_default(backend, from, to).redefine_sql(
+                backend,
+                &self.name,
+                rename_variant(&before, from, to),
+            ));
+        }
+        sql
+    }
+}
+
+fn rename_variant(variants: &[String], from: &str, to: &str) -> Vec<String> {
+    variants
+        .iter()
+        .map(|v| if v == from { to } else { v }.to_string())
+        .collect()
+}
+
+impl Operation for RenameEnumValue {
+    fn forward(&self, backend: &dyn Backend) -> Vec<String> {
+        self.rename_sql(backend, &self.old_value, &self.new_value)
+    }
+
+    fn backward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        Some(self.rename_sql(backend, &self.new_value, &self.old_value))
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "Rename value {} to {} in enum {}",
+            self.old_value, self.new_value, self.name
+        )
+    }
+
+    fn check(&self, backend: &dyn Backend) -> Result<(), String> {
+        check_enum_columns(backend, &self.name, &self.columns)
+    }
+
+    fn touches(&self) -> Vec<Touch> {
+        enum_column_touches(&self.columns)
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        state.update_enum(&self.name, |variants| {
+            *variants = rename_variant(variants, &self.old_value, &self.new_value)
+        });
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{MySql, Postgres, Sqlite};
+    use crate::operation::CreateTable;
+
+    fn status_field() -> Field {
+        Field::new(
+            "status",
+            FieldType::enumeration("order_status", &["pending", "shipped"]),
+        )
+        .not_null()
+        .default(crate::field::DefaultValue::string("pending"))
+    }
+
+    #[test]
+    fn enum_columns_per_backend() {
+        let op = CreateTable::new("orders").add_field(status_field());
+
+        assert!(op.forward(&Postgres)[0].contains("\"status\" \"order_status\" NOT NULL"));
+        assert!(op.forward(&MySql)[0].contains("`status` ENUM('pending', 'shipped') NOT NULL"));
+        assert!(op.forward(&Sqlite)[0]
+            .contains("\"status\" text CHECK (\"status\" IN ('pending', 'shipped')) NOT NULL DEFAULT 'pending'"));
+    }
+
+    #[test]
+    fn create_and_drop_enum() {
+        let op = CreateEnum::new("order_status", &["pending", "it's"]);
+        assert_eq!(
+            op.forward(&Postgres),
+            vec!["CREATE TYPE \"order_status\" AS ENUM ('pending', E'it\\'s')"]
+        );
+        assert_eq!(
+            op.backward(&Postgres).unwrap(),
+            vec!["DROP TYPE \"order_status\""]
+        );
+        assert!(op.forward(&MySql).is_empty());
+        assert!(op.forward(&Sqlite).is_empty());
+
+        let drop = DropEnum::new("order_status");
+        assert!(!drop.is_reversible());
+        let drop = drop.with_variants(&["pending"]);
+        assert_eq!(
+            drop.backward(&Postgres).unwrap(),
+            vec!["CREATE TYPE \"order_status\" AS ENUM ('pending')"]
+        );
+    }
+
+    #[test]
+    fn add_enum_value() {
+        let op = AddEnumValue::new("order_status", "paid")
+            .after("pending")
+            .column("orders", status_field());
+
+        assert_eq!(
+            op.forward(&Postgres),
+            vec!["ALTER TYPE \"order_status\" ADD VALUE 'paid' AFTER 'pending'"]
+        );
+        assert_eq!(
+            op.forward(&MySql),
+            vec!["ALTER TABLE `orders` MODIFY COLUMN `status` ENUM('pending', 'paid', 'shipped') NOT NULL DEFAULT 'pending'"]
+        );
+        assert_eq!(
+            op.backward(&MySql).unwrap(),
+            vec!["ALTER TABLE `orders` MODIFY COLUMN `status` ENUM('pending', 'shipped') NOT NULL DEFAULT 'pending'"]
+        );
+        assert!(op.backward(&Postgres).is_none());
+        assert!(op.forward(&Sqlite).is_empty());
+        assert_eq!(
+            op.check(&Sqlite).unwrap_err(),
+            "enum column orders.status can't be changed in place on sqlite; rebuild the table with RunSql"
+        );
+        assert!(op.check(&MySql).is_ok());
+        assert_eq!(
+            AddEnumValue::new("order_status", "paid")
+                .check(&MySql)
+                .unwrap_err(),
+            "enum order_status has no columns to change on mysql; list them with .column()"
+        );
+        assert!(AddEnumValue::new("order_status", "paid")
+            .check(&Postgres)
+            .is_ok());
+        assert!(!op.is_atomic());
+        assert!(!op.is_reversible());
+    }
+
+    #[test]
+    fn rename_enum_value() {
+        let op = RenameEnumValue::new("order_status", "shipped", "sent")
+            .column("orders", status_field());
+
+        assert_eq!(
+            op.forward(&Postgres),
+            vec!["ALTER TYPE \"order_status\" RENAME VALUE 'shipped' TO 'sent'"]
+        );
+        assert_eq!(
+            op.backward(&Postgres).unwrap(),
+            vec!["ALTER TYPE \"order_status\" RENAME VALUE 'sent' TO 'shipped'"]
+        );
+        assert_eq!(
+            op.forward(&MySql),
+            vec![
+                "ALTER TABLE `orders` MODIFY COLUMN `status` ENUM('pending', 'shipped', 'sent') NOT NULL DEFAULT 'pending'",
+                "UPDATE `orders` SET `status` = 'sent' WHERE `status` = 'shipped'",
+                "ALTER TABLE `orders` MODIFY COLUMN `status` ENUM('pending', 'sent') NOT NULL DEFAULT 'pending'",
+            ]
+        );
+        assert_eq!(
+            op.backward(&MySql).unwrap()[1],
+            "UPDATE `orders` SET `status` = 'shipped' WHERE `status` = 'sent'"
+        );
+        assert!(op.forward(&Sqlite).is_empty());
+        assert_eq!(op.backward(&Sqlite).unwrap(), Vec::<String>::new());
+        assert!(op.check(&Sqlite).is_err());
+        assert!(op.check(&Postgres).is_ok());
+    }
+
+    #[test]
+    fn rename_enum_value_renames_the_default() {
+        let op = RenameEnumValue::new("order_status", "pending", "waiting")
+            .column("orders", status_field());
+
+        assert_eq!(
+            op.forward(&MySql)[2],
+            "ALTER TABLE `orders` MODIFY COLUMN `status` ENUM('waiting', 'shipped') NOT NULL DEFAULT 'waiting'"
+        );
+        assert_eq!(
+            op.backward(&MySql).unwrap()[2],
+            "ALTER TABLE `orders` MODIFY COLUMN `status` ENUM('pending', 'shipped') NOT NULL DEFAULT 'pending'"
+        );
+    }
+
+    #[test]
+    fn enum_changes_replay_into_state() {
+        let mut state = SchemaState::new();
+        CreateTable::new("orders")
+            .add_field(status_field())
+            .state_forward(&mut state);
+        AddEnumValue::new("order_status", "paid")
+            .before("shipped")
+            .state_forward(&mut state);
+        RenameEnumValue::new("order_status", "shipped", "sent").state_forward(&mut state);
+
+        let status = state.table("orders").unwrap().field("status").unwrap();
+        assert_eq!(
+            status.field_type,
+            FieldType::enumeration("order_status", &["pending", "paid", "sent"])
+        );
+    }
+}
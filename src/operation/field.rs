@@ -1,8 +1,12 @@
 use crate::backend::{Backend, FieldChanges};
-use crate::field::{Field, FieldType};
-use crate::operation::Operation;
+use crate::field::{DefaultValue, Field, FieldType};
+#[cfg(feature = "serde")]
+use crate::operation::SerializedOperation;
+use crate::operation::{Operation, Touch};
+use crate::schema::SchemaState;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddField {
     pub table: String,
     pub field: Field,
@@ -34,12 +38,30 @@ impl Operation for AddField {
         format!("Add field {} to {}", self.field.name, self.table)
     }
 
+    fn touches(&self) -> Vec<Touch> {
+        let mut touches = vec![Touch::alter(&self.table, Some(&self.field.name))];
+        touches.extend(Touch::field_reference(&self.field));
+        touches
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        if let Some(table) = state.table_mut(&self.table) {
+            table.fields.push(self.field.clone());
+        }
+    }
+
     fn is_reversible(&self) -> bool {
         true
     }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RemoveField {
     pub table: String,
     pub field_name: String,
@@ -76,12 +98,28 @@ impl Operation for RemoveField {
         format!("Remove field {} from {}", self.field_name, self.table)
     }
 
+    fn touches(&self) -> Vec<Touch> {
+        vec![Touch::alter(&self.table, Some(&self.field_name))]
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        if let Some(table) = state.table_mut(&self.table) {
+            table.fields.retain(|f| f.name != self.field_name);
+        }
+    }
+
     fn is_reversible(&self) -> bool {
         self.field.is_some()
     }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RenameField {
     pub table: String,
     pub old_name: String,
@@ -117,9 +155,26 @@ impl Operation for RenameField {
             self.old_name, self.new_name, self.table
         )
     }
+
+    fn touches(&self) -> Vec<Touch> {
+        vec![
+            Touch::alter(&self.table, Some(&self.old_name)),
+            Touch::alter(&self.table, Some(&self.new_name)),
+        ]
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        state.rename_field(&self.table, &self.old_name, &self.new_name);
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AlterField {
     pub table: String,
     pub field_name: String,
@@ -147,7 +202,7 @@ impl AlterField {
         self
     }
 
-    pub fn set_default(mut self, default: Option<String>) -> Self {
+    pub fn set_default(mut self, default: Option<DefaultValue>) -> Self {
         self.changes.default = Some(default);
         self
     }
@@ -173,15 +228,33 @@ impl Operation for AlterField {
         format!("Alter field {} on {}", self.field_name, self.table)
     }
 
+    fn touches(&self) -> Vec<Touch> {
+        vec![Touch::alter(&self.table, Some(&self.field_name))]
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        if let Some(field) = state
+            .table_mut(&self.table)
+            .and_then(|t| t.field_mut(&self.field_name))
+        {
+            self.changes.apply(field);
+        }
+    }
+
     fn is_reversible(&self) -> bool {
         self.reverse_changes.is_some()
     }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::backend::{Postgres, Sqlite};
+    use crate::backend::{MySql, Postgres, Sqlite};
 
     #[test]
     fn add_field_generates_sql() {
@@ -287,7 +360,8 @@ mod tests {
 
     #[test]
     fn alter_field_set_default() {
-        let op = AlterField::new("users", "status").set_default(Some("'active'".to_string()));
+        let op =
+            AlterField::new("users", "status").set_default(Some(DefaultValue::string("active")));
 
         let sql = op.forward(&Postgres);
         assert!(sql[0].contains("ALTER TABLE"));
@@ -326,12 +400,20 @@ mod tests {
         let op = AlterField::new("users", "score")
             .set_type(FieldType::BigInt)
             .set_nullable(false)
-            .set_default(Some("0".to_string()));
+            .set_default(Some(0.into()));
 
         let sql = op.forward(&Postgres);
         assert!(sql[0].contains("\"score\""));
         assert!(sql[0].contains("NOT NULL"));
-        assert!(sql[0].contains("DEFAULT"));
+        assert!(sql[0].contains("DEFAULT 0"));
+    }
+
+    #[test]
+    fn alter_field_default_rendered_per_backend() {
+        let op = AlterField::new("users", "active").set_default(Some(true.into()));
+
+        assert!(op.forward(&Postgres)[0].contains("DEFAULT TRUE"));
+        assert!(op.forward(&MySql)[0].contains("DEFAULT 1"));
     }
 
     #[test]
@@ -1,7 +1,12 @@
 use crate::backend::Backend;
-use crate::operation::Operation;
+#[cfg(feature = "serde")]
+use crate::operation::SerializedOperation;
+use crate::operation::{Operation, Touch};
+use crate::schema::SchemaState;
 
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum IndexOrder {
     #[default]
     Asc,
@@ -9,13 +14,55 @@ pub enum IndexOrder {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Index {
     pub name: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "serde_support::deserialize_columns")
+    )]
     pub columns: Vec<(String, IndexOrder)>,
+    #[cfg_attr(feature = "serde", serde(default))]
     pub unique: bool,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
     pub where_clause: Option<String>,
 }
 
+#[cfg(feature = "serde")]
+mod serde_support {
+    use serde::{Deserialize, Deserializer};
+
+    use super::IndexOrder;
+
+    /// Index columns may be written as a bare name (ascending)
+    /// or as a `[name, order]` pair.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum IndexColumn {
+        Name(String),
+        Ordered(String, IndexOrder),
+    }
+
+    pub fn deserialize_columns<'de, D>(
+        deserializer: D,
+    ) -> Result<Vec<(String, IndexOrder)>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let columns = Vec::<IndexColumn>::deserialize(deserializer)?;
+        Ok(columns
+            .into_iter()
+            .map(|column| match column {
+                IndexColumn::Name(name) => (name, IndexOrder::Asc),
+                IndexColumn::Ordered(name, order) => (name, order),
+            })
+            .collect())
+    }
+}
+
 impl Index {
     pub fn new(name: impl Into<String>) -> Self {
         Self {
@@ -50,6 +97,7 @@ impl Index {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AddIndex {
     pub table: String,
     pub index: Index,
@@ -76,9 +124,26 @@ impl Operation for AddIndex {
     fn describe(&self) -> String {
         format!("Add index {} on {}", self.index.name, self.table)
     }
+
+    fn touches(&self) -> Vec<Touch> {
+        index_touches(&self.table, Some(&self.index))
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        if let Some(table) = state.table_mut(&self.table) {
+            table.indexes.retain(|i| i.name != self.index.name);
+            table.indexes.push(self.index.clone());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RemoveIndex {
     pub table: String,
     pub name: String,
@@ -115,9 +180,36 @@ impl Operation for RemoveIndex {
         format!("Remove index {} from {}", self.name, self.table)
     }
 
+    fn touches(&self) -> Vec<Touch> {
+        index_touches(&self.table, self.index.as_ref())
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        if let Some(table) = state.table_mut(&self.table) {
+            table.indexes.retain(|i| i.name != self.name);
+        }
+    }
+
     fn is_reversible(&self) -> bool {
         self.index.is_some()
     }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
+}
+
+/// Index columns are altered touches; without a definition only the table is known.
+fn index_touches(table: &str, index: Option<&Index>) -> Vec<Touch> {
+    match index {
+        Some(index) => index
+            .columns
+            .iter()
+            .map(|(column, _)| Touch::alter(table, Some(column)))
+            .collect(),
+        None => vec![Touch::alter(table, None)],
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,349 @@
+use sea_query::{Alias, Cond, Expr, Query, SimpleExpr, Value};
+
+use crate::backend::Backend;
+#[cfg(feature = "serde")]
+use crate::operation::SerializedOperation;
+use crate::operation::{Operation, Touch};
+
+/// Insert seed rows, e.g. for lookup tables. The reverse deletes the same
+/// rows by their primary key, which must be among the inserted columns.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InsertRows {
+    pub table: String,
+    pub columns: Vec<String>,
+    /// Serialized as values tagged with their type, e.g. `{ "int": 1 }`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            serialize_with = "serialize_rows",
+            deserialize_with = "deserialize_rows"
+        )
+    )]
+    pub rows: Vec<Vec<Value>>,
+    /// Defaults to `id`.
+    pub primary_key: Vec<String>,
+}
+
+impl InsertRows {
+    pub fn new(table: impl Into<String>, columns: &[&str]) -> Self {
+        Self {
+            table: table.into(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: Vec::new(),
+            primary_key: vec!["id".to_string()],
+        }
+    }
+
+    /// Add a row with one value per column, e.g. `vec!["US".into(), 1.into()]`.
+    pub fn row(mut self, values: Vec<Value>) -> Self {
+        assert_eq!(
+            values.len(),
+            self.columns.len(),
+            "InsertRows: row for '{}' has {} values for {} columns",
+            self.table,
+            values.len(),
+            self.columns.len()
+        );
+        self.rows.push(values);
+        self
+    }
+
+    pub fn rows(self, rows: impl IntoIterator<Item = Vec<Value>>) -> Self {
+        rows.into_iter().fold(self, Self::row)
+    }
+
+    /// The columns that identify a row for the reverse `DELETE`.
+    pub fn primary_key(mut self, columns: &[&str]) -> Self {
+        self.primary_key = columns.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// `None` when there's no key or a key column isn't inserted, so rows
+    /// can't be told apart.
+    fn key_positions(&self) -> Option<Vec<usize>> {
+        if self.primary_key.is_empty() {
+            return None;
+        }
+        self.primary_key
+            .iter()
+            .map(|key| self.columns.iter().position(|c| c == key))
+            .collect()
+    }
+}
+
+impl Operation for InsertRows {
+    fn forward(&self, backend: &dyn Backend) -> Vec<String> {
+        if self.rows.is_empty() {
+            return Vec::new();
+        }
+        let mut stmt = Query::insert();
+        stmt.into_table(Alias::new(&self.table))
+            .columns(self.columns.iter().map(Alias::new));
+        for row in &self.rows {
+            stmt.values_panic(row.iter().cloned().map(SimpleExpr::from));
+        }
+        vec![backend.build_insert(stmt)]
+    }
+
+    fn backward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        let positions = self.key_positions()?;
+        if self.rows.is_empty() {
+            return Some(Vec::new());
+        }
+        let matches_row = |row: &Vec<Value>| {
+            positions.iter().fold(Cond::all(), |cond, &i| {
+                let column = Expr::col(Alias::new(&self.columns[i]));
+                if row[i] == row[i].as_null() {
+                    cond.add(column.is_null())
+                } else {
+                    cond.add(column.eq(row[i].clone()))
+                }
+            })
+        };
+        let mut stmt = Query::delete();
+        stmt.from_table(Alias::new(&self.table)).cond_where(
+            self.rows
+                .iter()
+                .fold(Cond::any(), |cond, row| cond.add(matches_row(row))),
+        );
+        Some(vec![backend.build_delete(stmt)])
+    }
+
+    fn describe(&self) -> String {
+        format!("Insert {} rows into {}", self.rows.len(), self.table)
+    }
+
+    fn is_reversible(&self) -> bool {
+        self.key_positions().is_some()
+    }
+
+    /// Rows loaded from migration files skip the width check in `row`.
+    fn check(&self, _backend: &dyn Backend) -> Result<(), String> {
+        match self.rows.iter().find(|row| row.len() != self.columns.len()) {
+            Some(row) => Err(format!(
+                "row for '{}' has {} values for {} columns",
+                self.table,
+                row.len(),
+                self.columns.len()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn touches(&self) -> Vec<Touch> {
+        vec![Touch::reference(&self.table, None)]
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
+}
+
+/// A `Value` as stored in migration files: tagged with its type so it
+/// restores exactly, with `null` for a typed NULL.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ValueRepr {
+    Bool(Option<bool>),
+    TinyInt(Option<i8>),
+    SmallInt(Option<i16>),
+    Int(Option<i32>),
+    BigInt(Option<i64>),
+    TinyUnsigned(Option<u8>),
+    SmallUnsigned(Option<u16>),
+    Unsigned(Option<u32>),
+    BigUnsigned(Option<u64>),
+    Float(Option<f32>),
+    Double(Option<f64>),
+    String(Option<String>),
+    Char(Option<char>),
+    Bytes(Option<Vec<u8>>),
+}
+
+#[cfg(feature = "serde")]
+impl ValueRepr {
+    // Other variants exist only with sea-query features this crate doesn't enable.
+    #[allow(unreachable_patterns)]
+    fn of(value: &Value) -> Result<Self, String> {
+        Ok(match value {
+            Value::Bool(v) => ValueRepr::Bool(*v),
+            Value::TinyInt(v) => ValueRepr::TinyInt(*v),
+            Value::SmallInt(v) => ValueRepr::SmallInt(*v),
+            Value::Int(v) => ValueRepr::Int(*v),
+            Value::BigInt(v) => ValueRepr::BigInt(*v),
+            Value::TinyUnsigned(v) => ValueRepr::TinyUnsigned(*v),
+            Value::SmallUnsigned(v) => ValueRepr::SmallUnsigned(*v),
+            Value::Unsigned(v) => ValueRepr::Unsigned(*v),
+            Value::BigUnsigned(v) => ValueRepr::BigUnsigned(*v),
+            Value::Float(v) => ValueRepr::Float(*v),
+            Value::Double(v) => ValueRepr::Double(*v),
+            Value::String(v) => ValueRepr::String(v.as_deref().cloned()),
+            Value::Char(v) => ValueRepr::Char(*v),
+            Value::Bytes(v) => ValueRepr::Bytes(v.as_deref().cloned()),
+            other => return Err(format!("unsupported value type: {:?}", other)),
+        })
+    }
+
+    fn into_value(self) -> Value {
+        match self {
+            ValueRepr::Bool(v) => Value::Bool(v),
+            ValueRepr::TinyInt(v) => Value::TinyInt(v),
+            ValueRepr::SmallInt(v) => Value::SmallInt(v),
+            ValueRepr::Int(v) => Value::Int(v),
+            ValueRepr::BigInt(v) => Value::BigInt(v),
+            ValueRepr::TinyUnsigned(v) => Value::TinyUnsigned(v),
+            ValueRepr::SmallUnsigned(v) => Value::SmallUnsigned(v),
+            ValueRepr::Unsigned(v) => Value::Unsigned(v),
+            ValueRepr::BigUnsigned(v) => Value::BigUnsigned(v),
+            ValueRepr::Float(v) => Value::Float(v),
+            ValueRepr::Double(v) => Value::Double(v),
+            ValueRepr::String(v) => Value::String(v.map(Box::new)),
+            ValueRepr::Char(v) => Value::Char(v),
+            ValueRepr::Bytes(v) => Value::Bytes(v.map(Box::new)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn serialize_rows<S>(rows: &[Vec<Value>], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let rows = rows
+        .iter()
+        .map(|row| row.iter().map(ValueRepr::of).collect())
+        .collect::<Result<Vec<Vec<_>>, _>>()
+        .map_err(serde::ser::Error::custom)?;
+    serde::Serialize::serialize(&rows, serializer)
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_rows<'de, D>(deserializer: D) -> Result<Vec<Vec<Value>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let rows: Vec<Vec<ValueRepr>> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(rows
+        .into_iter()
+        .map(|row| row.into_iter().map(ValueRepr::into_value).collect())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{MySql, Postgres, Sqlite};
+
+    fn countries() -> InsertRows {
+        InsertRows::new("countries", &["code", "name", "eu"])
+            .primary_key(&["code"])
+            .row(vec!["FR".into(), "France".into(), true.into()])
+            .row(vec!["NO".into(), "Norway".into(), false.into()])
+    }
+
+    #[test]
+    fn insert_rows_per_backend() {
+        assert_eq!(
+            countries().forward(&Postgres),
+            vec![
+                "INSERT INTO \"countries\" (\"code\", \"name\", \"eu\") VALUES ('FR', 'France', TRUE), ('NO', 'Norway', FALSE)"
+            ]
+        );
+        assert_eq!(
+            countries().forward(&MySql),
+            vec![
+                "INSERT INTO `countries` (`code`, `name`, `eu`) VALUES ('FR', 'France', TRUE), ('NO', 'Norway', FALSE)"
+            ]
+        );
+    }
+
+    #[test]
+    fn reverse_deletes_by_primary_key() {
+        assert_eq!(
+            countries().backward(&Sqlite).unwrap(),
+            vec!["DELETE FROM \"countries\" WHERE \"code\" = 'FR' OR \"code\" = 'NO'"]
+        );
+
+        let op = InsertRows::new("role_grants", &["role_id", "grant_id"])
+            .primary_key(&["role_id", "grant_id"])
+            .row(vec![1.into(), 2.into()])
+            .row(vec![1.into(), 3.into()]);
+        assert_eq!(
+            op.backward(&Postgres).unwrap(),
+            vec![
+                "DELETE FROM \"role_grants\" WHERE (\"role_id\" = 1 AND \"grant_id\" = 2) OR (\"role_id\" = 1 AND \"grant_id\" = 3)"
+            ]
+        );
+    }
+
+    #[test]
+    fn irreversible_without_key_columns() {
+        let op = InsertRows::new("roles", &["name"]).row(vec!["admin".into()]);
+        assert!(!op.is_reversible());
+        assert!(op.backward(&Sqlite).is_none());
+    }
+
+    #[test]
+    fn irreversible_with_empty_key() {
+        let op = InsertRows::new("roles", &["id"])
+            .primary_key(&[])
+            .row(vec![1.into()]);
+        assert!(!op.is_reversible());
+        assert!(op.backward(&Sqlite).is_none());
+    }
+
+    #[test]
+    fn reverse_matches_null_keys() {
+        let op = InsertRows::new("settings", &["scope", "name"])
+            .primary_key(&["scope", "name"])
+            .row(vec![Value::String(None), "theme".into()]);
+        assert_eq!(
+            op.backward(&Sqlite).unwrap(),
+            vec!["DELETE FROM \"settings\" WHERE \"scope\" IS NULL AND \"name\" = 'theme'"]
+        );
+    }
+
+    #[test]
+    fn check_rejects_rows_of_the_wrong_width() {
+        let mut op = InsertRows::new("roles", &["id", "name"]);
+        op.rows.push(vec![1.into()]);
+        assert_eq!(
+            op.check(&Sqlite),
+            Err("row for 'roles' has 1 values for 2 columns".to_string())
+        );
+    }
+
+    #[test]
+    fn empty_insert_generates_nothing() {
+        let op = InsertRows::new("roles", &["id"]);
+        assert!(op.forward(&Sqlite).is_empty());
+        assert_eq!(op.backward(&Sqlite), Some(vec![]));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn rows_serialize_as_tagged_values() {
+        let op = InsertRows::new("roles", &["id", "name", "parent_id"]).row(vec![
+            1.into(),
+            "admin".into(),
+            Value::Int(None),
+        ]);
+
+        let json = serde_json::to_value(&op).unwrap();
+        assert_eq!(
+            json["rows"],
+            serde_json::json!([[{ "int": 1 }, { "string": "admin" }, { "int": null }]])
+        );
+        let restored: InsertRows = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.rows, op.rows);
+    }
+
+    #[test]
+    #[should_panic(expected = "has 1 values for 2 columns")]
+    fn row_width_must_match_columns() {
+        let _ = InsertRows::new("roles", &["id", "name"]).row(vec![1.into()]);
+    }
+}
@@ -1,16 +1,94 @@
+mod backfill;
+mod code;
 mod constraint;
+mod enums;
 mod field;
 mod index;
+mod insert;
+mod script;
+#[cfg(feature = "serde")]
+mod serialization;
 mod sql;
 mod table;
 
+pub use backfill::Backfill;
+pub use code::{RunCode, RunContext};
 pub use constraint::{AddConstraint, Constraint, RemoveConstraint};
+pub use enums::{
+    AddEnumValue, CreateEnum, DropEnum, EnumColumn, EnumValuePosition, RenameEnumValue,
+};
 pub use field::{AddField, AlterField, RemoveField, RenameField};
 pub use index::{AddIndex, Index, IndexOrder, RemoveIndex};
-pub use sql::RunSql;
+pub use insert::InsertRows;
+pub use script::{split_statements, ScriptSyntax};
+pub use sea_query::Value;
+#[cfg(feature = "serde")]
+pub use serialization::{OperationRegistry, SerializableOperation, SerializedOperation};
+pub use sql::{RunSql, Statement};
 pub use table::{CreateTable, DropTable, RenameTable};
 
 use crate::backend::Backend;
+use crate::field::Field;
+use crate::schema::SchemaState;
+
+/// How an operation affects a table or column, as seen by static analysis
+/// of a registry (merge conflicts, dependency inference).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Touch {
+    /// Creates a table, or gives an existing table this name.
+    CreateTable(String),
+    /// Drops a table, or renames it away.
+    DropTable(String),
+    /// Changes a table. `column` is `None` for changes that aren't tied to
+    /// particular columns, like check constraints.
+    Alter {
+        table: String,
+        column: Option<String>,
+    },
+    /// Relies on a table without changing it, e.g. the target of a foreign key.
+    Reference {
+        table: String,
+        column: Option<String>,
+    },
+}
+
+impl Touch {
+    pub fn table(&self) -> &str {
+        match self {
+            Touch::CreateTable(table) | Touch::DropTable(table) => table,
+            Touch::Alter { table, .. } | Touch::Reference { table, .. } => table,
+        }
+    }
+
+    pub fn column(&self) -> Option<&str> {
+        match self {
+            Touch::CreateTable(_) | Touch::DropTable(_) => None,
+            Touch::Alter { column, .. } | Touch::Reference { column, .. } => column.as_deref(),
+        }
+    }
+
+    pub(crate) fn alter(table: &str, column: Option<&str>) -> Self {
+        Touch::Alter {
+            table: table.to_string(),
+            column: column.map(str::to_string),
+        }
+    }
+
+    pub(crate) fn reference(table: &str, column: Option<&str>) -> Self {
+        Touch::Reference {
+            table: table.to_string(),
+            column: column.map(str::to_string),
+        }
+    }
+
+    /// The foreign key target of a field, if it has one.
+    pub(crate) fn field_reference(field: &Field) -> Option<Self> {
+        field
+            .references
+            .as_ref()
+            .map(|fk| Touch::reference(&fk.table, Some(&fk.column)))
+    }
+}
 
 pub trait Operation: Send + Sync {
     fn forward(&self, backend: &dyn Backend) -> Vec<String>;
@@ -19,7 +97,86 @@ pub trait Operation: Send + Sync {
 
     fn describe(&self) -> String;
 
+    /// Forward SQL with bind values. The default wraps `forward` without any.
+    fn forward_statements(&self, backend: &dyn Backend) -> Vec<Statement> {
+        self.forward(backend)
+            .into_iter()
+            .map(Statement::new)
+            .collect()
+    }
+
+    /// Backward SQL with bind values. The default wraps `backward` without any.
+    fn backward_statements(&self, backend: &dyn Backend) -> Option<Vec<Statement>> {
+        self.backward(backend)
+            .map(|sqls| sqls.into_iter().map(Statement::new).collect())
+    }
+
+    /// Like `forward`, but `None` when no SQL can be built for `backend`
+    /// instead of panicking.
+    fn try_forward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        Some(self.forward(backend))
+    }
+
+    /// Like `backward`, but also `None` when the reverse can't be built for
+    /// `backend` instead of panicking.
+    fn try_backward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        self.backward(backend)
+    }
+
     fn is_reversible(&self) -> bool {
         true
     }
+
+    /// Whether this operation may run inside a transaction. A migration
+    /// containing a non-atomic operation is non-atomic itself.
+    fn is_atomic(&self) -> bool {
+        true
+    }
+
+    /// Tables and columns this operation affects. Operations returning an
+    /// empty list (the default, and `RunSql`) are invisible to analysis.
+    fn touches(&self) -> Vec<Touch> {
+        Vec::new()
+    }
+
+    /// Apply this operation to an in-memory schema. The default leaves the
+    /// state unchanged, so such operations are invisible to replay.
+    fn state_forward(&self, _state: &mut SchemaState) {}
+
+    /// Code to run when the migration is applied, right after this
+    /// operation's `forward` SQL. The default does nothing.
+    fn run_forward(&self, _context: &mut RunContext<'_>) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Code to run when the migration is rolled back, right after this
+    /// operation's `backward` SQL. The default does nothing.
+    fn run_backward(&self, _context: &mut RunContext<'_>) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Why this operation can't run on `backend`, if it can't. Checked before
+    /// a migration executes anything, so it fails without partial changes.
+    fn check(&self, _backend: &dyn Backend) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Whether `run_forward`/`run_backward` do anything. Code can't be
+    /// captured as SQL, so such migrations aren't stored for ghost rollback.
+    fn runs_code(&self) -> bool {
+        false
+    }
+
+    /// Whether squashing may drop this operation, e.g. a data fix that only
+    /// matters for databases that already hold rows.
+    fn is_elidable(&self) -> bool {
+        false
+    }
+
+    /// Serialize this operation for storage or export. Operations returning
+    /// `None` (the default) cannot be round-tripped through an `OperationRegistry`.
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        None
+    }
 }
@@ -0,0 +1,447 @@
+use std::ops::Range;
+
+use crate::backend::Backend;
+
+/// The lexical rules a backend's SQL scripts follow, as far as they matter
+/// for finding where one statement ends and the next begins.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScriptSyntax {
+    /// `$$ ... $$` and `$tag$ ... $tag$` quoted bodies (PostgreSQL).
+    pub dollar_quotes: bool,
+    /// Backslash escapes inside string literals (MySQL).
+    pub backslash_escapes: bool,
+    /// `#` line comments (MySQL).
+    pub hash_comments: bool,
+    /// `/* /* */ */` block comments nest (PostgreSQL).
+    pub nested_comments: bool,
+    /// `DELIMITER //` lines change the statement terminator (MySQL client
+    /// syntax, used around procedure and trigger bodies).
+    pub delimiter_command: bool,
+    /// `CREATE TRIGGER ... BEGIN ...; END;` bodies only end at `END;` (SQLite).
+    pub trigger_bodies: bool,
+    /// `CREATE PROCEDURE|FUNCTION|TRIGGER|EVENT ... BEGIN ...; END` bodies
+    /// written without `DELIMITER` only end after their `END` (MySQL).
+    pub routine_bodies: bool,
+}
+
+/// Split a SQL script into its statements using `backend`'s syntax.
+///
+/// Semicolons inside string literals, quoted identifiers, comments,
+/// dollar-quoted bodies and trigger bodies don't end a statement. The
+/// terminators themselves, `DELIMITER` lines and pieces holding only
+/// whitespace or comments are dropped; each statement is trimmed.
+pub fn split_statements(backend: &dyn Backend, sql: &str) -> Vec<String> {
+    Splitter::new(sql, backend.script_syntax()).split()
+}
+
+/// A bind placeholder in a statement: `$n` or a bare `?`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Placeholder {
+    pub range: Range<usize>,
+    /// `n` for `$n`, `None` for `?`.
+    pub index: Option<usize>,
+}
+
+/// The placeholders in `sql`, skipping strings, comments and dollar-quoted
+/// bodies the same way the splitter does.
+pub(crate) fn placeholders(syntax: ScriptSyntax, sql: &str) -> Vec<Placeholder> {
+    let mut splitter = Splitter::new(sql, syntax);
+    splitter.scan();
+    splitter.placeholders
+}
+
+struct Splitter<'s> {
+    sql: &'s str,
+    bytes: &'s [u8],
+    syntax: ScriptSyntax,
+    delimiter: String,
+    pos: usize,
+    start: usize,
+    /// Whether the current statement has anything besides comments.
+    has_code: bool,
+    /// The first word of the current statement, uppercased.
+    first_word: String,
+    /// The kind of object a `CREATE` statement makes, when it can have a
+    /// `BEGIN ... END` body (`TRIGGER`, `PROCEDURE`, ...).
+    body_kind: Option<&'static str>,
+    last_word: String,
+    /// Open `BEGIN`/`CASE` blocks in the current statement, each closed by
+    /// an `END`.
+    block_depth: usize,
+    statements: Vec<String>,
+    placeholders: Vec<Placeholder>,
+}
+
+impl<'s> Splitter<'s> {
+    fn new(sql: &'s str, syntax: ScriptSyntax) -> Self {
+        Self {
+            sql,
+            bytes: sql.as_bytes(),
+            syntax,
+            delimiter: ";".to_string(),
+            pos: 0,
+            start: 0,
+            has_code: false,
+            first_word: String::new(),
+            body_kind: None,
+            last_word: String::new(),
+            block_depth: 0,
+            statements: Vec::new(),
+            placeholders: Vec::new(),
+        }
+    }
+
+    fn split(mut self) -> Vec<String> {
+        self.scan();
+        self.statements
+    }
+
+    fn scan(&mut self) {
+        while self.pos < self.bytes.len() {
+            if !self.has_code && self.syntax.delimiter_command && self.delimiter_command() {
+                continue;
+            }
+            if self.bytes[self.pos..].starts_with(self.delimiter.as_bytes()) {
+                let end = self.pos;
+                self.pos += self.delimiter.len();
+                if !self.in_body() {
+                    self.finish(end);
+                }
+                continue;
+            }
+
+            let byte = self.bytes[self.pos];
+            let next = self.bytes.get(self.pos + 1).copied();
+            match byte {
+                b'\'' | b'"' => self.quoted(byte, self.syntax.backslash_escapes),
+                b'`' => self.quoted(byte, false),
+                b'-' if next == Some(b'-') => self.line_comment(),
+                b'#' if self.syntax.hash_comments => self.line_comment(),
+                b'/' if next == Some(b'*') => self.block_comment(),
+                b'$' => self.dollar(),
+                b'?' => {
+                    self.placeholders.push(Placeholder {
+                        range: self.pos..self.pos + 1,
+                        index: None,
+                    });
+                    self.has_code = true;
+                    self.pos += 1;
+                }
+                b if b.is_ascii_alphabetic() || b == b'_' => self.word(),
+                b => {
+                    self.has_code |= !b.is_ascii_whitespace();
+                    self.pos += 1;
+                }
+            }
+        }
+        self.finish(self.bytes.len());
+    }
+
+    fn finish(&mut self, end: usize) {
+        if self.has_code {
+            self.statements
+                .push(self.sql[self.start..end].trim().to_string());
+        }
+        self.start = self.pos;
+        self.has_code = false;
+        self.first_word.clear();
+        self.body_kind = None;
+        self.last_word.clear();
+        self.block_depth = 0;
+    }
+
+    /// Whether a terminator here is inside a `BEGIN ... END` body.
+    fn in_body(&self) -> bool {
+        let has_body = match self.body_kind {
+            Some("TRIGGER") => self.syntax.trigger_bodies || self.syntax.routine_bodies,
+            Some(_) => self.syntax.routine_bodies,
+            None => false,
+        };
+        has_body && self.block_depth > 0
+    }
+
+    /// Handle a `DELIMITER <terminator>` line at the start of a statement.
+    fn delimiter_command(&mut self) -> bool {
+        const COMMAND: &[u8] = b"DELIMITER";
+        let is_command = self.bytes[self.pos..]
+            .get(..COMMAND.len() + 1)
+            .is_some_and(|word| {
+                word[..COMMAND.len()].eq_ignore_ascii_case(COMMAND)
+                    && matches!(word[COMMAND.len()], b' ' | b'\t')
+            });
+        if !is_command {
+            return false;
+        }
+
+        let rest = &self.sql[self.pos..];
+        let line_end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+        if let Some(delimiter) = rest[COMMAND.len()..line_end].split_whitespace().next() {
+            self.delimiter = delimiter.to_string();
+        }
+        self.pos += line_end;
+        self.start = self.pos;
+        true
+    }
+
+    fn quoted(&mut self, quote: u8, backslash_escapes: bool) {
+        self.has_code = true;
+        self.pos += 1;
+        while let Some(&byte) = self.bytes.get(self.pos) {
+            self.pos += 1;
+            if byte == b'\\' && backslash_escapes {
+                self.pos += 1;
+            } else if byte == quote {
+                if self.bytes.get(self.pos) != Some(&quote) {
+                    return;
+                }
+                self.pos += 1;
+            }
+        }
+    }
+
+    fn line_comment(&mut self) {
+        self.pos = self.sql[self.pos..]
+            .find('\n')
+            .map_or(self.bytes.len(), |i| self.pos + i + 1);
+    }
+
+    fn block_comment(&mut self) {
+        let mut depth = 0;
+        while self.pos < self.bytes.len() {
+            match &self.bytes[self.pos..] {
+                [b'/', b'*', ..] if depth == 0 || self.syntax.nested_comments => {
+                    depth += 1;
+                    self.pos += 2;
+                }
+                [b'*', b'/', ..] => {
+                    depth -= 1;
+                    self.pos += 2;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => self.pos += 1,
+            }
+        }
+    }
+
+    /// A `$1` placeholder, or a `$tag$` that opens a quoted body unless it's
+    /// part of an identifier. Tags can't start with a digit.
+    fn dollar(&mut self) {
+        self.has_code = true;
+        let follows_identifier = self.pos > 0 && is_identifier_byte(self.bytes[self.pos - 1]);
+        let digits = self.bytes[self.pos + 1..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        if digits > 0 && !follows_identifier {
+            let end = self.pos + 1 + digits;
+            if let Ok(index) = self.sql[self.pos + 1..end].parse() {
+                self.placeholders.push(Placeholder {
+                    range: self.pos..end,
+                    index: Some(index),
+                });
+            }
+            self.pos = end;
+            return;
+        }
+        if self.syntax.dollar_quotes && !follows_identifier {
+            self.dollar_quoted();
+        } else {
+            self.pos += 1;
+        }
+    }
+
+    fn dollar_quoted(&mut self) {
+        let tag_len = self.bytes[self.pos + 1..]
+            .iter()
+            .position(|&b| !(b.is_ascii_alphanumeric() || b == b'_'))
+            .unwrap_or(self.bytes.len() - self.pos - 1);
+        let opens_tag = self.bytes.get(self.pos + 1 + tag_len) == Some(&b'$');
+        if !opens_tag {
+            self.pos += 1;
+            return;
+        }
+
+        let tag = &self.sql[self.pos..self.pos + tag_len + 2];
+        let body = self.pos + tag.len();
+        self.pos = self.sql[body..]
+            .find(tag)
+            .map_or(self.bytes.len(), |i| body + i + tag.len());
+    }
+
+    fn word(&mut self) {
+        self.has_code = true;
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|&b| is_identifier_byte(b))
+        {
+            self.pos += 1;
+        }
+        let word = self.sql[start..self.pos].to_ascii_uppercase();
+
+        // PostgreSQL's E'...' strings take backslash escapes.
+        if word == "E" && self.syntax.dollar_quotes && self.bytes.get(self.pos) == Some(&b'\'') {
+            self.quoted(b'\'', true);
+            return;
+        }
+        if self.first_word.is_empty() {
+            self.first_word = word.clone();
+        }
+        if self.first_word == "CREATE" && self.body_kind.is_none() {
+            self.body_kind = BODY_KINDS.into_iter().find(|&kind| kind == word);
+        }
+
+        // `END` closes a `BEGIN` or `CASE`. MySQL also ends `IF`, `LOOP`,
+        // `WHILE` and `REPEAT` blocks with `END IF` etc.; those don't open a
+        // block (`IF` may be the function), so their `END` is given back.
+        match (self.last_word.as_str(), word.as_str()) {
+            ("END", "IF" | "LOOP" | "WHILE" | "REPEAT") => self.block_depth += 1,
+            ("END", "CASE") => {}
+            (_, "BEGIN" | "CASE") => self.block_depth += 1,
+            (_, "END") => self.block_depth = self.block_depth.saturating_sub(1),
+            _ => {}
+        }
+        self.last_word = word;
+    }
+}
+
+/// Objects whose `CREATE` statement may have a `BEGIN ... END` body.
+const BODY_KINDS: [&str; 4] = ["TRIGGER", "PROCEDURE", "FUNCTION", "EVENT"];
+
+fn is_identifier_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{MySql, Postgres, Sqlite};
+
+    #[test]
+    fn splits_on_semicolons() {
+        assert_eq!(
+            split_statements(
+                &Sqlite,
+                "CREATE TABLE a (id INT);\n\nINSERT INTO a VALUES (1);\n"
+            ),
+            vec!["CREATE TABLE a (id INT)", "INSERT INTO a VALUES (1)"]
+        );
+        assert_eq!(split_statements(&Sqlite, "SELECT 1"), vec!["SELECT 1"]);
+        assert!(split_statements(&Sqlite, " ;\n-- nothing here;\n").is_empty());
+    }
+
+    #[test]
+    fn ignores_semicolons_in_strings_and_comments() {
+        let sql = "INSERT INTO t VALUES ('a;b', \"c;d\", `e;f`, 'it''s;'); -- trailing; comment\n\
+                   /* block; comment */ SELECT 2";
+        assert_eq!(
+            split_statements(&Sqlite, sql),
+            vec![
+                "INSERT INTO t VALUES ('a;b', \"c;d\", `e;f`, 'it''s;')",
+                "-- trailing; comment\n/* block; comment */ SELECT 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn postgres_dollar_quotes_and_nested_comments() {
+        let sql = "CREATE FUNCTION f() RETURNS trigger AS $body$\n\
+                   BEGIN NEW.a := 'x;'; RETURN NEW; END;\n\
+                   $body$ LANGUAGE plpgsql;\n\
+                   /* outer /* inner; */ still comment; */\n\
+                   DO $$ BEGIN PERFORM 1; END $$;\n\
+                   SELECT E'a\\';b', price$1 FROM t WHERE id = $1";
+        assert_eq!(
+            split_statements(&Postgres, sql),
+            vec![
+                "CREATE FUNCTION f() RETURNS trigger AS $body$\nBEGIN NEW.a := 'x;'; RETURN NEW; END;\n$body$ LANGUAGE plpgsql",
+                "/* outer /* inner; */ still comment; */\nDO $$ BEGIN PERFORM 1; END $$",
+                "SELECT E'a\\';b', price$1 FROM t WHERE id = $1",
+            ]
+        );
+    }
+
+    #[test]
+    fn mysql_delimiter_blocks() {
+        let sql = "DELIMITER //\n\
+                   CREATE PROCEDURE p() BEGIN SELECT 'a\\';'; SELECT 2; END//\n\
+                   delimiter ;\n\
+                   # hash; comment\n\
+                   SELECT 3;";
+        assert_eq!(
+            split_statements(&MySql, sql),
+            vec![
+                "CREATE PROCEDURE p() BEGIN SELECT 'a\\';'; SELECT 2; END",
+                "# hash; comment\nSELECT 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn mysql_bodies_without_delimiter() {
+        let sql = "CREATE PROCEDURE p() BEGIN\n\
+                   IF (SELECT IF(n > 0, 1, 0) FROM a) THEN SELECT 1; END IF;\n\
+                   CASE WHEN 1 THEN SELECT 2; END CASE;\n\
+                   SELECT 3;\n\
+                   END;\n\
+                   CREATE TABLE events (event int, `function` text);\n\
+                   BEGIN;\n\
+                   SELECT 4;";
+        assert_eq!(
+            split_statements(&MySql, sql),
+            vec![
+                "CREATE PROCEDURE p() BEGIN\nIF (SELECT IF(n > 0, 1, 0) FROM a) THEN SELECT 1; END IF;\nCASE WHEN 1 THEN SELECT 2; END CASE;\nSELECT 3;\nEND",
+                "CREATE TABLE events (event int, `function` text)",
+                "BEGIN",
+                "SELECT 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn sqlite_trigger_bodies() {
+        let sql = "CREATE TEMP TRIGGER t AFTER INSERT ON a BEGIN\n\
+                   INSERT INTO log VALUES (1); UPDATE a SET n = n + 1;\n\
+                   END;\n\
+                   SELECT 1;";
+        assert_eq!(
+            split_statements(&Sqlite, sql),
+            vec![
+                "CREATE TEMP TRIGGER t AFTER INSERT ON a BEGIN\nINSERT INTO log VALUES (1); UPDATE a SET n = n + 1;\nEND",
+                "SELECT 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn sqlite_case_inside_trigger_body() {
+        let sql = "CREATE TRIGGER t AFTER UPDATE ON a BEGIN\n\
+                   UPDATE a SET size = CASE WHEN n > 10 THEN 'big' ELSE 'small' END;\n\
+                   INSERT INTO log VALUES (CASE new.n WHEN 0 THEN 'zero' END);\n\
+                   END;\n\
+                   SELECT 1;";
+        assert_eq!(
+            split_statements(&Sqlite, sql),
+            vec![
+                "CREATE TRIGGER t AFTER UPDATE ON a BEGIN\nUPDATE a SET size = CASE WHEN n > 10 THEN 'big' ELSE 'small' END;\nINSERT INTO log VALUES (CASE new.n WHEN 0 THEN 'zero' END);\nEND",
+                "SELECT 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn dialect_rules_stay_with_their_backend() {
+        // Backslashes are literal in standard strings, and `#` is an operator.
+        assert_eq!(
+            split_statements(&Postgres, "SELECT 'a\\'; SELECT 1 # 2; SELECT 3"),
+            vec!["SELECT 'a\\'", "SELECT 1 # 2", "SELECT 3"]
+        );
+        assert_eq!(
+            split_statements(&Sqlite, "SELECT $$a;b$$"),
+            vec!["SELECT $$a", "b$$"]
+        );
+    }
+}
@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::operation::{
+    AddConstraint, AddEnumValue, AddField, AddIndex, AlterField, Backfill, CreateEnum, CreateTable,
+    DropEnum, DropTable, InsertRows, Operation, RemoveConstraint, RemoveField, RemoveIndex,
+    RenameEnumValue, RenameField, RenameTable, RunSql,
+};
+
+/// An operation serialized together with the kind needed to restore it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerializedOperation {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub data: serde_json::Value,
+}
+
+impl SerializedOperation {
+    /// Serialize a concrete operation. Implementations of
+    /// `Operation::to_serialized` usually just call this.
+    pub fn of<T: SerializableOperation>(op: &T) -> Option<Self> {
+        Some(Self {
+            kind: T::KIND.to_string(),
+            data: serde_json::to_value(op).ok()?,
+        })
+    }
+}
+
+/// An operation that can be stored and restored through an [`OperationRegistry`].
+///
+/// `KIND` must be unique among registered operations. To make a `Box<dyn Operation>`
+/// serializable, also override `Operation::to_serialized`:
+///
+/// ```ignore
+/// fn to_serialized(&self) -> Option<SerializedOperation> {
+///     SerializedOperation::of(self)
+/// }
+/// ```
+pub trait SerializableOperation: Operation + Serialize + DeserializeOwned + 'static {
+    const KIND: &'static str;
+}
+
+type DeserializeFn = fn(serde_json::Value) -> Result<Box<dyn Operation>, String>;
+
+fn deserialize_as<T: SerializableOperation>(
+    data: serde_json::Value,
+) -> Result<Box<dyn Operation>, String> {
+    serde_json::from_value::<T>(data)
+        .map(|op| Box::new(op) as Box<dyn Operation>)
+        .map_err(|e| format!("{}: {}", T::KIND, e))
+}
+
+/// Maps operation kinds to deserializers so `Box<dyn Operation>` values,
+/// including user-defined operations, can be restored.
+#[derive(Default)]
+pub struct OperationRegistry {
+    deserializers: HashMap<&'static str, DeserializeFn>,
+}
+
+impl OperationRegistry {
+    /// Create an empty registry with no operation kinds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry that knows every built-in operation.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register::<CreateTable>();
+        registry.register::<DropTable>();
+        registry.register::<RenameTable>();
+        registry.register::<AddField>();
+        registry.register::<RemoveField>();
+        registry.register::<RenameField>();
+        registry.register::<AlterField>();
+        registry.register::<AddIndex>();
+        registry.register::<RemoveIndex>();
+        registry.register::<AddConstraint>();
+        registry.register::<RemoveConstraint>();
+        registry.register::<RunSql>();
+        registry.register::<CreateEnum>();
+        registry.register::<DropEnum>();
+        registry.register::<AddEnumValue>();
+        registry.register::<RenameEnumValue>();
+        registry.register::<InsertRows>();
+        registry.register::<Backfill>();
+        registry
+    }
+
+    pub fn register<T: SerializableOperation>(&mut self) {
+        self.deserializers.insert(T::KIND, deserialize_as::<T>);
+    }
+
+    pub fn contains(&self, kind: &str) -> bool {
+        self.deserializers.contains_key(kind)
+    }
+
+    pub fn serialize(&self, op: &dyn Operation) -> Result<SerializedOperation, String> {
+        op.to_serialized()
+            .ok_or_else(|| format!("Operation is not serializable: {}", op.describe()))
+    }
+
+    pub fn deserialize(&self, op: &SerializedOperation) -> Result<Box<dyn Operation>, String> {
+        let deserialize = self
+            .deserializers
+            .get(op.kind.as_str())
+            .ok_or_else(|| format!("Unknown operation type: {}", op.kind))?;
+        deserialize(op.data.clone())
+    }
+
+    pub fn serialize_all(
+        &self,
+        ops: &[Box<dyn Operation>],
+    ) -> Result<Vec<SerializedOperation>, String> {
+        ops.iter().map(|op| self.serialize(op.as_ref())).collect()
+    }
+
+    pub fn deserialize_all(
+        &self,
+        ops: &[SerializedOperation],
+    ) -> Result<Vec<Box<dyn Operation>>, String> {
+        ops.iter().map(|op| self.deserialize(op)).collect()
+    }
+}
+
+macro_rules! serializable_operations {
+    ($($op:ty => $kind:literal),* $(,)?) => {
+        $(
+            impl SerializableOperation for $op {
+                const KIND: &'static str = $kind;
+            }
+        )*
+    };
+}
+
+serializable_operations! {
+    CreateTable => "create_table",
+    DropTable => "drop_table",
+    RenameTable => "rename_table",
+    AddField => "add_field",
+    RemoveField => "remove_field",
+    RenameField => "rename_field",
+    AlterField => "alter_field",
+    AddIndex => "add_index",
+    RemoveIndex => "remove_index",
+    AddConstraint => "add_constraint",
+    RemoveConstraint => "remove_constraint",
+    RunSql => "run_sql",
+    CreateEnum => "create_enum",
+    DropEnum => "drop_enum",
+    AddEnumValue => "add_enum_value",
+    RenameEnumValue => "rename_enum_value",
+    InsertRows => "insert_rows",
+    Backfill => "backfill",
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{Backend, FieldChanges, MySql, Postgres};
+    use crate::field::{DefaultValue, Field, FieldType, ReferentialAction, SqlType};
+    use crate::operation::{Constraint, Index, Value};
+
+    fn builtin_operations() -> Vec<Box<dyn Operation>> {
+        vec![
+            Box::new(
+                CreateTable::new("users")
+                    .add_field(Field::new("id", FieldType::Serial).primary_key())
+                    .add_field(
+                        Field::new("org_id", FieldType::Integer)
+                            .references("orgs", "id")
+                            .on_delete(ReferentialAction::Cascade),
+                    )
+                    .add_field(Field::new(
+                        "balance",
+                        FieldType::Decimal {
+                            precision: 10,
+                            scale: 2,
+                        },
+                    )),
+            ),
+            Box::new(
+                DropTable::new("legacy").with_fields(vec![Field::new("id", FieldType::Serial)]),
+            ),
+            Box::new(RenameTable::new("people", "persons")),
+            Box::new(CreateEnum::new("mood", &["happy", "sad"])),
+            Box::new(AddEnumValue::new("mood", "calm").before("sad").column(
+                "users",
+                Field::new("mood", FieldType::enumeration("mood", &["happy", "sad"])),
+            )),
+            Box::new(RenameEnumValue::new("mood", "sad", "blue")),
+            Box::new(DropEnum::new("mood").with_variants(&["happy", "calm", "blue"])),
+            Box::new(AddField::new(
+                "users",
+                Field::new("status", FieldType::VarChar(16)).default("'active'"),
+            )),
+            Box::new(AddField::new(
+                "users",
+                Field::new("token", FieldType::Uuid).default(DefaultValue::GeneratedUuid),
+            )),
+            Box::new(AddField::new(
+                "users",
+                Field::new(
+                    "location",
+                    FieldType::custom(SqlType::new("geometry(Point, 4326)").on("mysql", "point")),
+                ),
+            )),
+            Box::new(
+                RemoveField::new("users", "bio")
+                    .with_definition(Field::new("bio", FieldType::Text)),
+            ),
+            Box::new(RenameField::new("users", "name", "full_name")),
+            Box::new(
+                AlterField::new("users", "status")
+                    .set_nullable(false)
+                    .set_default(None)
+                    .with_reverse(
+                        FieldChanges::new()
+                            .set_nullable(true)
+                            .set_default(Some(DefaultValue::string("new"))),
+                    ),
+            ),
+            Box::new(AddIndex::new(
+                "users",
+                Index::new("idx_users_status")
+                    .column("status")
+                    .column_desc("id")
+                    .filter("status <> 'deleted'"),
+            )),
+            Box::new(RemoveIndex::new("users", "idx_old")),
+            Box::new(AddConstraint::new(
+                "users",
+                Constraint::foreign_key(
+                    "fk_users_org",
+                    vec!["org_id".to_string()],
+                    "orgs",
+                    vec!["id".to_string()],
+                ),
+            )),
+            Box::new(
+                RemoveConstraint::new("users", "chk_age")
+                    .with_definition(Constraint::check("chk_age", "age >= 0")),
+            ),
+            Box::new(
+                InsertRows::new("roles", &["id", "name", "weight"])
+                    .row(vec![1.into(), "admin".into(), 2.5.into()])
+                    .row(vec![2.into(), "guest".into(), Value::Double(None)]),
+            ),
+            Box::new(
+                Backfill::new("users", "status = 'active'", 1..=1000)
+                    .batch_size(100)
+                    .with_reverse("status = NULL"),
+            ),
+            Box::new(
+                RunSql::portable()
+                    .for_backend_reversible("postgres", "ANALYZE users", "SELECT 1")
+                    .for_backend_reversible("mysql", "ANALYZE TABLE users", "SELECT 1")
+                    .with_description("Analyze"),
+            ),
+        ]
+    }
+
+    fn sql(ops: &[Box<dyn Operation>], backend: &dyn Backend) -> Vec<Vec<String>> {
+        ops.iter()
+            .flat_map(|op| {
+                [
+                    op.forward(backend),
+                    op.backward(backend).unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn builtin_operations_round_trip() {
+        let registry = OperationRegistry::with_builtins();
+        let ops = builtin_operations();
+
+        let serialized = registry.serialize_all(&ops).unwrap();
+        let json = serde_json::to_string(&serialized).unwrap();
+        let decoded: Vec<SerializedOperation> = serde_json::from_str(&json).unwrap();
+        let restored = registry.deserialize_all(&decoded).unwrap();
+
+        assert_eq!(restored.len(), ops.len());
+        for (original, restored) in ops.iter().zip(&restored) {
+            assert_eq!(original.describe(), restored.describe());
+            assert_eq!(original.is_reversible(), restored.is_reversible());
+        }
+        assert_eq!(sql(&ops, &Postgres), sql(&restored, &Postgres));
+        assert_eq!(sql(&ops, &MySql), sql(&restored, &MySql));
+    }
+
+    #[test]
+    fn defaults_serialize_typed_and_accept_plain_sql() {
+        let field = Field::new("active", FieldType::Boolean).default(true);
+        assert_eq!(
+            serde_json::to_value(&field).unwrap()["default"],
+            serde_json::json!({ "bool": true })
+        );
+
+        let field: Field = serde_json::from_value(serde_json::json!({
+            "name": "created_at",
+            "type": "timestamp",
+            "default": "current_timestamp"
+        }))
+        .unwrap();
+        assert_eq!(field.default, Some(DefaultValue::CurrentTimestamp));
+
+        let changes: FieldChanges =
+            serde_json::from_value(serde_json::json!({ "default": "'active'" })).unwrap();
+        assert_eq!(changes.default, Some(Some(DefaultValue::raw("'active'"))));
+    }
+
+    #[test]
+    fn custom_types_serialize_as_sql() {
+        let citext = FieldType::custom(SqlType::new("citext").on("sqlite", "text"));
+        assert_eq!(
+            serde_json::to_value(&citext).unwrap(),
+            serde_json::json!({ "custom": { "name": "citext", "backends": { "sqlite": "text" } } })
+        );
+
+        let field: Field = serde_json::from_value(serde_json::json!({
+            "name": "tag",
+            "type": { "custom": "citext" }
+        }))
+        .unwrap();
+        assert_eq!(field.field_type, citext);
+        assert_eq!(
+            Postgres.add_field_sql("t", &field),
+            vec!["ALTER TABLE \"t\" ADD COLUMN \"tag\" citext"]
+        );
+    }
+
+    #[test]
+    fn serialized_operation_is_tagged_with_kind() {
+        let op = RenameTable::new("a", "b");
+        let serialized = SerializedOperation::of(&op).unwrap();
+
+        assert_eq!(serialized.kind, "rename_table");
+        assert_eq!(
+            serde_json::to_value(&serialized).unwrap(),
+            serde_json::json!({
+                "type": "rename_table",
+                "data": { "old_name": "a", "new_name": "b" }
+            })
+        );
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Vacuum {
+        full: bool,
+    }
+
+    impl Operation for Vacuum {
+        fn forward(&self, _backend: &dyn Backend) -> Vec<String> {
+            vec![if self.full { "VACUUM FULL" } else { "VACUUM" }.to_string()]
+        }
+
+        fn backward(&self, _backend: &dyn Backend) -> Option<Vec<String>> {
+            Some(vec![])
+        }
+
+        fn describe(&self) -> String {
+            "Vacuum".to_string()
+        }
+
+        fn to_serialized(&self) -> Option<SerializedOperation> {
+            SerializedOperation::of(self)
+        }
+    }
+
+    impl SerializableOperation for Vacuum {
+        const KIND: &'static str = "vacuum";
+    }
+
+    #[test]
+    fn user_defined_operation_round_trip() {
+        let mut registry = OperationRegistry::with_builtins();
+        registry.register::<Vacuum>();
+        assert!(registry.contains("vacuum"));
+
+        let op: Box<dyn Operation> = Box::new(Vacuum { full: true });
+        let serialized = registry.serialize(op.as_ref()).unwrap();
+        let restored = registry.deserialize(&serialized).unwrap();
+
+        assert_eq!(restored.forward(&Postgres), vec!["VACUUM FULL"]);
+    }
+
+    #[test]
+    fn unknown_kind_fails() {
+        let registry = OperationRegistry::with_builtins();
+        let result = registry.deserialize(&SerializedOperation {
+            kind: "vacuum".to_string(),
+            data: serde_json::json!({ "full": true }),
+        });
+
+        assert_eq!(result.err().unwrap(), "Unknown operation type: vacuum");
+    }
+
+    #[test]
+    fn invalid_data_fails() {
+        let registry = OperationRegistry::with_builtins();
+        let result = registry.deserialize(&SerializedOperation {
+            kind: "rename_table".to_string(),
+            data: serde_json::json!({ "old_name": "a" }),
+        });
+
+        assert!(result.err().unwrap().starts_with("rename_table:"));
+    }
+
+    struct Opaque;
+
+    impl Operation for Opaque {
+        fn forward(&self, _backend: &dyn Backend) -> Vec<String> {
+            vec![]
+        }
+
+        fn backward(&self, _backend: &dyn Backend) -> Option<Vec<String>> {
+            None
+        }
+
+        fn describe(&self) -> String {
+            "Opaque".to_string()
+        }
+    }
+
+    #[test]
+    fn operation_without_serialization_fails() {
+        let registry = OperationRegistry::with_builtins();
+        let result = registry.serialize(&Opaque);
+
+        assert_eq!(
+            result.err().unwrap(),
+            "Operation is not serializable: Opaque"
+        );
+    }
+}
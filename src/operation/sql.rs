@@ -1,9 +1,112 @@
 use std::collections::HashMap;
 
+use sea_query::Value;
+
 use crate::backend::Backend;
-use crate::operation::Operation;
+use crate::operation::script::placeholders;
+#[cfg(feature = "serde")]
+use crate::operation::SerializedOperation;
+use crate::operation::{split_statements, Operation};
+
+/// A SQL statement with bind values for its placeholders, in the
+/// backend's placeholder syntax.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub sql: String,
+    pub values: Vec<Value>,
+}
+
+impl Statement {
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self {
+            sql: sql.into(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Rewrite `$1`-style placeholders in `sql` to `backend`'s syntax (`$n`
+    /// on PostgreSQL, `?` elsewhere), taking the values they refer to from
+    /// `values`. Each occurrence gets its own placeholder, so values may be
+    /// referenced repeatedly or out of order. Placeholders inside quoted
+    /// strings, comments and dollar-quoted bodies are left alone.
+    ///
+    /// Panics if a placeholder has no value; see [`Statement::try_bind`].
+    pub fn bind(backend: &dyn Backend, sql: &str, values: &[Value]) -> Self {
+        Self::try_bind(backend, sql, values).unwrap_or_else(|e| panic!("Statement: {}", e))
+    }
+
+    /// Like [`Statement::bind`], but reports a placeholder without a value.
+    pub fn try_bind(backend: &dyn Backend, sql: &str, values: &[Value]) -> Result<Self, String> {
+        if values.is_empty() {
+            return Ok(Self::new(sql));
+        }
+
+        let mut bound = Self::new(String::with_capacity(sql.len()));
+        let mut copied = 0;
+        for placeholder in placeholders(backend.script_syntax(), sql) {
+            let Some(index) = placeholder.index else {
+                continue;
+            };
+            let value = index
+                .checked_sub(1)
+                .and_then(|i| values.get(i))
+                .ok_or_else(|| format!("no value for placeholder ${} in: {}", index, sql))?;
+            bound.values.push(value.clone());
+            bound.sql.push_str(&sql[copied..placeholder.range.start]);
+            bound.sql.push_str(&backend.placeholder(bound.values.len()));
+            copied = placeholder.range.end;
+        }
+        bound.sql.push_str(&sql[copied..]);
+        Ok(bound)
+    }
+
+    /// The statement with its values inlined as literals.
+    pub fn inline(&self, backend: &dyn Backend) -> String {
+        if self.values.is_empty() {
+            return self.sql.clone();
+        }
+
+        // Find the placeholders with the script lexer so `?` and `$n` in
+        // strings, comments and bodies stay as they are.
+        let positional = backend.placeholder(1) == "?";
+        let mut sql = String::with_capacity(self.sql.len());
+        let mut copied = 0;
+        let mut next = 0;
+        for placeholder in placeholders(backend.script_syntax(), &self.sql) {
+            let position = match placeholder.index {
+                None if positional => next,
+                Some(n) if !positional && n > 0 => n - 1,
+                _ => continue,
+            };
+            let Some(value) = self.values.get(position) else {
+                continue;
+            };
+            next += 1;
+            sql.push_str(&self.sql[copied..placeholder.range.start]);
+            sql.push_str(
+                &backend.inline_values(&backend.placeholder(1), std::slice::from_ref(value)),
+            );
+            copied = placeholder.range.end;
+        }
+        sql.push_str(&self.sql[copied..]);
+        sql
+    }
+}
+
+impl From<String> for Statement {
+    fn from(sql: String) -> Self {
+        Self::new(sql)
+    }
+}
+
+impl From<&str> for Statement {
+    fn from(sql: &str) -> Self {
+        Self::new(sql)
+    }
+}
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum SqlSource {
     Static {
         sql: Vec<String>,
@@ -29,10 +132,17 @@ impl SqlSource {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RunSql {
     forward: SqlSource,
     backward: Option<SqlSource>,
     description: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    values: Vec<Value>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    reverse_values: Vec<Value>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    unsplit: bool,
 }
 
 impl RunSql {
@@ -45,6 +155,9 @@ impl RunSql {
             },
             backward: None,
             description: "Run custom SQL".to_string(),
+            values: Vec::new(),
+            reverse_values: Vec::new(),
+            unsplit: false,
         }
     }
 
@@ -57,6 +170,9 @@ impl RunSql {
             },
             backward: None,
             description: "Run custom SQL".to_string(),
+            values: Vec::new(),
+            reverse_values: Vec::new(),
+            unsplit: false,
         }
     }
 
@@ -72,6 +188,9 @@ impl RunSql {
                 only_backends: None,
             }),
             description: "Run custom SQL".to_string(),
+            values: Vec::new(),
+            reverse_values: Vec::new(),
+            unsplit: false,
         }
     }
 
@@ -83,6 +202,9 @@ impl RunSql {
             forward: SqlSource::ByBackend(HashMap::new()),
             backward: None,
             description: "Run portable SQL".to_string(),
+            values: Vec::new(),
+            reverse_values: Vec::new(),
+            unsplit: false,
         }
     }
 
@@ -173,29 +295,120 @@ impl RunSql {
         self.description = description.into();
         self
     }
+
+    /// Run each SQL string as given instead of splitting it into statements,
+    /// for drivers that take whole scripts and syntax the splitter doesn't
+    /// know.
+    pub fn unsplit(mut self) -> Self {
+        self.unsplit = true;
+        self
+    }
+
+    /// Add a bind value for the forward SQL, referenced as `$1`, `$2`, ...
+    /// in order of binding. Executors that take parameters receive it
+    /// separately; elsewhere it is inlined as a literal.
+    pub fn bind(mut self, value: impl Into<Value>) -> Self {
+        self.values.push(value.into());
+        self
+    }
+
+    /// Add a bind value for the reverse SQL, numbered separately from the
+    /// forward values.
+    pub fn bind_reverse(mut self, value: impl Into<Value>) -> Self {
+        self.reverse_values.push(value.into());
+        self
+    }
+}
+
+impl RunSql {
+    /// Split the scripts into statements and bind `values`, which are shared
+    /// by all of them.
+    fn statements(
+        &self,
+        backend: &dyn Backend,
+        sqls: Vec<String>,
+        values: &[Value],
+    ) -> Result<Vec<Statement>, String> {
+        let sqls = if self.unsplit {
+            sqls
+        } else {
+            sqls.iter()
+                .flat_map(|sql| split_statements(backend, sql))
+                .collect()
+        };
+        sqls.iter()
+            .map(|sql| Statement::try_bind(backend, sql, values))
+            .collect()
+    }
+}
+
+fn inline_all(backend: &dyn Backend, statements: Vec<Statement>) -> Vec<String> {
+    statements.iter().map(|s| s.inline(backend)).collect()
 }
 
 impl Operation for RunSql {
     fn forward(&self, backend: &dyn Backend) -> Vec<String> {
-        self.forward.resolve(backend).unwrap_or_else(|| {
+        inline_all(backend, self.forward_statements(backend))
+    }
+
+    fn backward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        self.backward_statements(backend)
+            .map(|statements| inline_all(backend, statements))
+    }
+
+    fn forward_statements(&self, backend: &dyn Backend) -> Vec<Statement> {
+        let sqls = self.forward.resolve(backend).unwrap_or_else(|| {
             panic!(
                 "RunSql: no SQL configured for backend '{}'. Use for_backend() to add it.",
                 backend.name()
             )
-        })
+        });
+        self.statements(backend, sqls, &self.values)
+            .unwrap_or_else(|e| panic!("RunSql: {}", e))
     }
 
-    fn backward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+    fn backward_statements(&self, backend: &dyn Backend) -> Option<Vec<Statement>> {
         self.backward.as_ref().map(|b| {
-            b.resolve(backend).unwrap_or_else(|| {
+            let sqls = b.resolve(backend).unwrap_or_else(|| {
                 panic!(
                     "RunSql: no reverse SQL configured for backend '{}'. Use for_backend_reversible() to add it.",
                     backend.name()
                 )
-            })
+            });
+            self.statements(backend, sqls, &self.reverse_values)
+                .unwrap_or_else(|e| panic!("RunSql: {}", e))
         })
     }
 
+    fn try_forward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        let sqls = self.forward.resolve(backend)?;
+        let statements = self.statements(backend, sqls, &self.values).ok()?;
+        Some(inline_all(backend, statements))
+    }
+
+    fn try_backward(&self, backend: &dyn Backend) -> Option<Vec<String>> {
+        let sqls = self.backward.as_ref()?.resolve(backend)?;
+        let statements = self.statements(backend, sqls, &self.reverse_values).ok()?;
+        Some(inline_all(backend, statements))
+    }
+
+    /// Fails when the forward SQL is missing for `backend` or a placeholder
+    /// on either side has no bind value.
+    fn check(&self, backend: &dyn Backend) -> Result<(), String> {
+        let Some(sqls) = self.forward.resolve(backend) else {
+            return Err(format!(
+                "no SQL configured for backend '{}'",
+                backend.name()
+            ));
+        };
+        self.statements(backend, sqls, &self.values)?;
+        if let Some(sqls) = self.backward.as_ref().and_then(|b| b.resolve(backend)) {
+            self.statements(backend, sqls, &self.reverse_values)
+                .map_err(|e| format!("reverse SQL: {}", e))?;
+        }
+        Ok(())
+    }
+
     fn describe(&self) -> String {
         self.description.clone()
     }
@@ -203,12 +416,165 @@ impl Operation for RunSql {
     fn is_reversible(&self) -> bool {
         self.backward.is_some()
     }
+
+    /// Bind values aren't serializable, so parameterized SQL isn't either.
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        if !self.values.is_empty() || !self.reverse_values.is_empty() {
+            return None;
+        }
+        SerializedOperation::of(self)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::backend::Sqlite;
+    use crate::backend::{MySql, Postgres, Sqlite};
+
+    #[test]
+    fn bind_placeholders_per_backend() {
+        let sql = "UPDATE t SET a = $2, note = 'costs $1' WHERE id = $1 OR parent = $1";
+        let values: Vec<Value> = vec![7.into(), "x".into()];
+
+        assert_eq!(
+            Statement::bind(&Postgres, sql, &values),
+            Statement {
+                sql: "UPDATE t SET a = $1, note = 'costs $1' WHERE id = $2 OR parent = $3"
+                    .to_string(),
+                values: vec!["x".into(), 7.into(), 7.into()],
+            }
+        );
+        assert_eq!(
+            Statement::bind(&MySql, sql, &values).sql,
+            "UPDATE t SET a = ?, note = 'costs $1' WHERE id = ? OR parent = ?"
+        );
+        assert_eq!(
+            Statement::bind(&Sqlite, sql, &values).inline(&Sqlite),
+            "UPDATE t SET a = 'x', note = 'costs $1' WHERE id = 7 OR parent = 7"
+        );
+    }
+
+    #[test]
+    fn bind_without_values_leaves_sql_alone() {
+        let sql = "CREATE FUNCTION f() RETURNS int AS $$ SELECT $1 $$";
+        assert_eq!(Statement::bind(&Postgres, sql, &[]), Statement::new(sql));
+    }
+
+    #[test]
+    #[should_panic(expected = "no value for placeholder $2")]
+    fn bind_missing_value_panics() {
+        Statement::bind(&Postgres, "SELECT $1, $2", &[1.into()]);
+    }
+
+    #[test]
+    fn bind_skips_bodies_and_comments() {
+        let sql = "CREATE FUNCTION f(int) RETURNS int AS $$ SELECT $1 + $2 $$ LANGUAGE sql; \
+                   -- replaces $3\n\
+                   SELECT f($1)";
+        let bound = Statement::bind(&Postgres, sql, &[5.into()]);
+        assert_eq!(
+            bound.sql,
+            "CREATE FUNCTION f(int) RETURNS int AS $$ SELECT $1 + $2 $$ LANGUAGE sql; \
+             -- replaces $3\n\
+             SELECT f($1)"
+        );
+        assert_eq!(bound.values, vec![5.into()]);
+        assert_eq!(
+            bound.inline(&Postgres),
+            "CREATE FUNCTION f(int) RETURNS int AS $$ SELECT $1 + $2 $$ LANGUAGE sql; \
+             -- replaces $3\n\
+             SELECT f(5)"
+        );
+        assert_eq!(
+            Statement::bind(&Sqlite, "SELECT '?', $1 -- ?\n", &["a".into()]).inline(&Sqlite),
+            "SELECT '?', 'a' -- ?\n"
+        );
+    }
+
+    #[test]
+    fn run_sql_check_reports_missing_values() {
+        let op = RunSql::reversible("INSERT INTO t VALUES ($1)", "DELETE FROM t WHERE id = $2")
+            .bind(1)
+            .bind_reverse(1);
+
+        assert_eq!(
+            op.check(&Postgres),
+            Err("reverse SQL: no value for placeholder $2 in: DELETE FROM t WHERE id = $2".into())
+        );
+        assert_eq!(op.try_backward(&Postgres), None);
+        assert_eq!(
+            op.try_forward(&Postgres),
+            Some(vec!["INSERT INTO t VALUES (1)".to_string()])
+        );
+        assert!(RunSql::new("SELECT $1").bind(1).check(&Sqlite).is_ok());
+        assert!(RunSql::portable()
+            .for_backend("postgres", "SELECT 1")
+            .check(&Sqlite)
+            .is_err());
+    }
+
+    #[test]
+    fn run_sql_with_bind_values() {
+        let op = RunSql::reversible(
+            "INSERT INTO config (key, value) VALUES ($1, $2)",
+            "DELETE FROM config WHERE key = $1",
+        )
+        .bind("greeting")
+        .bind("it's")
+        .bind_reverse("greeting");
+
+        assert_eq!(
+            op.forward_statements(&Postgres),
+            vec![Statement {
+                sql: "INSERT INTO config (key, value) VALUES ($1, $2)".to_string(),
+                values: vec!["greeting".into(), "it's".into()],
+            }]
+        );
+        assert_eq!(
+            op.forward(&MySql),
+            vec!["INSERT INTO config (key, value) VALUES ('greeting', 'it\\'s')"]
+        );
+        assert_eq!(
+            op.backward_statements(&Sqlite).unwrap()[0].sql,
+            "DELETE FROM config WHERE key = ?"
+        );
+        assert_eq!(
+            op.backward(&Sqlite).unwrap(),
+            vec!["DELETE FROM config WHERE key = 'greeting'"]
+        );
+        #[cfg(feature = "serde")]
+        assert!(op.to_serialized().is_none());
+    }
+
+    #[test]
+    fn run_sql_splits_scripts_per_backend() {
+        let op = RunSql::new(
+            "CREATE FUNCTION touch() RETURNS trigger AS $$ BEGIN NEW.at := now(); RETURN NEW; END $$ LANGUAGE plpgsql;\n\
+             UPDATE t SET note = 'a;b' WHERE id = $1;\n",
+        )
+        .bind(5);
+
+        let statements = op.forward_statements(&Postgres);
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].sql.ends_with("END $$ LANGUAGE plpgsql"));
+        assert_eq!(
+            statements[1],
+            Statement {
+                sql: "UPDATE t SET note = 'a;b' WHERE id = $1".to_string(),
+                values: vec![5.into()],
+            }
+        );
+
+        let op = RunSql::new("DROP TABLE a; DROP TABLE b;")
+            .with_reverse("CREATE TABLE a (id INT); CREATE TABLE b (id INT)");
+        assert_eq!(op.forward(&Sqlite), vec!["DROP TABLE a", "DROP TABLE b"]);
+        assert_eq!(op.backward(&MySql).unwrap().len(), 2);
+        assert_eq!(
+            op.unsplit().forward(&Sqlite),
+            vec!["DROP TABLE a; DROP TABLE b;"]
+        );
+    }
 
     #[test]
     fn run_sql_forward() {
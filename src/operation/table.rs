@@ -1,8 +1,12 @@
 use crate::backend::Backend;
 use crate::field::{Field, FieldType};
-use crate::operation::Operation;
+#[cfg(feature = "serde")]
+use crate::operation::SerializedOperation;
+use crate::operation::{Operation, Touch};
+use crate::schema::{SchemaState, TableState};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CreateTable {
     pub name: String,
     pub fields: Vec<Field>,
@@ -39,9 +43,25 @@ impl Operation for CreateTable {
     fn describe(&self) -> String {
         format!("Create table {}", self.name)
     }
+
+    fn touches(&self) -> Vec<Touch> {
+        let mut touches = vec![Touch::CreateTable(self.name.clone())];
+        touches.extend(self.fields.iter().filter_map(Touch::field_reference));
+        touches
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        state.create_table(TableState::new(self.name.clone(), self.fields.clone()));
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DropTable {
     pub name: String,
     pub fields: Option<Vec<Field>>,
@@ -76,12 +96,26 @@ impl Operation for DropTable {
         format!("Drop table {}", self.name)
     }
 
+    fn touches(&self) -> Vec<Touch> {
+        vec![Touch::DropTable(self.name.clone())]
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        state.drop_table(&self.name);
+    }
+
     fn is_reversible(&self) -> bool {
         self.fields.is_some()
     }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RenameTable {
     pub old_name: String,
     pub new_name: String,
@@ -110,6 +144,22 @@ impl Operation for RenameTable {
     fn describe(&self) -> String {
         format!("Rename table {} to {}", self.old_name, self.new_name)
     }
+
+    fn touches(&self) -> Vec<Touch> {
+        vec![
+            Touch::DropTable(self.old_name.clone()),
+            Touch::CreateTable(self.new_name.clone()),
+        ]
+    }
+
+    fn state_forward(&self, state: &mut SchemaState) {
+        state.rename_table(&self.old_name, &self.new_name);
+    }
+
+    #[cfg(feature = "serde")]
+    fn to_serialized(&self) -> Option<SerializedOperation> {
+        SerializedOperation::of(self)
+    }
 }
 
 #[cfg(test)]
@@ -206,4 +256,26 @@ mod tests {
         let sql = op.forward(&Sqlite);
         assert_eq!(sql[0], "DROP TABLE \"users\"");
     }
+
+    #[test]
+    fn table_touches() {
+        let op = CreateTable::new("posts")
+            .add_field(Field::new("id", FieldType::Serial).primary_key())
+            .add_field(Field::new("author_id", FieldType::Integer).references("users", "id"));
+
+        assert_eq!(
+            op.touches(),
+            vec![
+                Touch::CreateTable("posts".to_string()),
+                Touch::reference("users", Some("id")),
+            ]
+        );
+        assert_eq!(
+            RenameTable::new("posts", "articles").touches(),
+            vec![
+                Touch::DropTable("posts".to_string()),
+                Touch::CreateTable("articles".to_string()),
+            ]
+        );
+    }
 }
@@ -0,0 +1,423 @@
+//! An in-memory schema built by replaying migrations.
+//!
+//! Each operation describes its effect through
+//! [`Operation::state_forward`](crate::operation::Operation::state_forward).
+//! Operations that don't (like `RunSql`) leave the state unchanged.
+
+use crate::field::{Field, FieldType};
+use crate::migration::{Migration, MigrationError, MigrationRegistry};
+use crate::operation::{Constraint, Index};
+
+#[derive(Debug, Clone)]
+pub struct TableState {
+    pub name: String,
+    pub fields: Vec<Field>,
+    pub indexes: Vec<Index>,
+    pub constraints: Vec<Constraint>,
+}
+
+/// A foreign key, declared on a field or as a table constraint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relation {
+    pub columns: Vec<String>,
+    pub ref_table: String,
+    pub ref_columns: Vec<String>,
+}
+
+impl TableState {
+    pub fn new(name: impl Into<String>, fields: Vec<Field>) -> Self {
+        Self {
+            name: name.into(),
+            fields,
+            indexes: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+
+    pub fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    pub fn field_mut(&mut self, name: &str) -> Option<&mut Field> {
+        self.fields.iter_mut().find(|f| f.name == name)
+    }
+
+    /// Rename a field along with the index and constraint columns naming it.
+    pub fn rename_field(&mut self, old_name: &str, new_name: &str) {
+        let rename = |column: &mut String| {
+            if column == old_name {
+                *column = new_name.to_string();
+            }
+        };
+
+        if let Some(field) = self.field_mut(old_name) {
+            field.name = new_name.to_string();
+        }
+        for index in &mut self.indexes {
+            index
+                .columns
+                .iter_mut()
+                .for_each(|(column, _)| rename(column));
+        }
+        for constraint in &mut self.constraints {
+            match constraint {
+                Constraint::Unique { columns, .. } | Constraint::ForeignKey { columns, .. } => {
+                    columns.iter_mut().for_each(rename)
+                }
+                Constraint::Check { .. } => {}
+            }
+        }
+    }
+
+    /// Foreign keys from field `references` and `ForeignKey` constraints.
+    pub fn relations(&self) -> Vec<Relation> {
+        let fields = self.fields.iter().filter_map(|field| {
+            let fk = field.references.as_ref()?;
+            Some(Relation {
+                columns: vec![field.name.clone()],
+                ref_table: fk.table.clone(),
+                ref_columns: vec![fk.column.clone()],
+            })
+        });
+        let constraints = self
+            .constraints
+            .iter()
+            .filter_map(|constraint| match constraint {
+                Constraint::ForeignKey {
+                    columns,
+                    ref_table,
+                    ref_columns,
+                    ..
+                } => Some(Relation {
+                    columns: columns.clone(),
+                    ref_table: ref_table.clone(),
+                    ref_columns: ref_columns.clone(),
+                }),
+                _ => None,
+            });
+        fields.chain(constraints).collect()
+    }
+}
+
+/// Tables in the order they were created.
+#[derive(Debug, Clone, Default)]
+pub struct SchemaState {
+    tables: Vec<TableState>,
+}
+
+impl SchemaState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replay every migration of the registry in dependency order.
+    pub fn from_registry(registry: &MigrationRegistry) -> Result<Self, MigrationError> {
+        let mut state = Self::new();
+        for name in registry.resolve_order()? {
+            let migration = registry
+                .get(name)
+                .ok_or_else(|| MigrationError::NotFound(name.to_string()))?;
+            state.apply(migration);
+        }
+        Ok(state)
+    }
+
+    /// Apply a migration's forward operations.
+    pub fn apply(&mut self, migration: &Migration) {
+        for op in migration.forward_operations() {
+            op.state_forward(self);
+        }
+    }
+
+    pub fn tables(&self) -> &[TableState] {
+        &self.tables
+    }
+
+    pub fn table(&self, name: &str) -> Option<&TableState> {
+        self.tables.iter().find(|t| t.name == name)
+    }
+
+    pub fn table_mut(&mut self, name: &str) -> Option<&mut TableState> {
+        self.tables.iter_mut().find(|t| t.name == name)
+    }
+
+    /// Add a table, replacing any table with the same name.
+    pub fn create_table(&mut self, table: TableState) {
+        self.drop_table(&table.name);
+        self.tables.push(table);
+    }
+
+    pub fn drop_table(&mut self, name: &str) {
+        self.tables.retain(|t| t.name != name);
+    }
+
+    /// Change the variants of every field typed as the enum `name`.
+    pub fn update_enum(&mut self, name: &str, update: impl Fn(&mut Vec<String>)) {
+        let fields = self.tables.iter_mut().flat_map(|t| t.fields.iter_mut());
+        for field in fields {
+            if let FieldType::Enum {
+                name: ref enum_name,
+                ref mut variants,
+            } = field.field_type
+            {
+                if enum_name == name {
+                    update(variants);
+                }
+            }
+        }
+    }
+
+    /// Rename a table and repoint foreign keys that reference it.
+    pub fn rename_table(&mut self, old_name: &str, new_name: &str) {
+        for table in &mut self.tables {
+            if table.name == old_name {
+                table.name = new_name.to_string();
+            }
+            for fk in table
+                .fields
+                .iter_mut()
+                .filter_map(|f| f.references.as_mut())
+            {
+                if fk.table == old_name {
+                    fk.table = new_name.to_string();
+                }
+            }
+            for constraint in &mut table.constraints {
+                if let Constraint::ForeignKey { ref_table, .. } = constraint {
+                    if ref_table == old_name {
+                        *ref_table = new_name.to_string();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Rename a field of `table` and repoint foreign keys that reference it.
+    pub fn rename_field(&mut self, table: &str, old_name: &str, new_name: &str) {
+        if let Some(state) = self.table_mut(table) {
+            state.rename_field(old_name, new_name);
+        }
+        let rename = |column: &mut String| {
+            if column == old_name {
+                *column = new_name.to_string();
+            }
+        };
+        for other in &mut self.tables {
+            for fk in other
+                .fields
+                .iter_mut()
+                .filter_map(|f| f.references.as_mut())
+                .filter(|fk| fk.table == table)
+            {
+                rename(&mut fk.column);
+            }
+            for constraint in &mut other.constraints {
+                if let Constraint::ForeignKey {
+                    ref_table,
+                    ref_columns,
+                    ..
+                } = constraint
+                {
+                    if ref_table == table {
+                        ref_columns.iter_mut().for_each(rename);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::FieldChanges;
+    use crate::field::SqlType;
+    use crate::operation::{
+        AddConstraint, AddField, AddIndex, AlterField, CreateTable, DropTable, RemoveConstraint,
+        RemoveField, RemoveIndex, RenameField, RenameTable, RunSql,
+    };
+
+    fn names(fields: &[Field]) -> Vec<&str> {
+        fields.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    #[test]
+    fn replay_registry() {
+        let mut registry = MigrationRegistry::new();
+        registry.register(
+            Migration::new("0001_initial")
+                .operation(
+                    CreateTable::new("users")
+                        .add_field(Field::new("id", FieldType::Serial).primary_key())
+                        .add_field(Field::new("email", FieldType::Text)),
+                )
+                .operation(
+                    CreateTable::new("posts")
+                        .add_field(Field::new("id", FieldType::Serial).primary_key())
+                        .add_field(
+                            Field::new("author_id", FieldType::Integer).references("users", "id"),
+                        ),
+                ),
+        );
+        registry.register(
+            Migration::new("0002_changes")
+                .depends_on(&["0001_initial"])
+                .operation(AddField::new("users", Field::new("name", FieldType::Text)))
+                .operation(RenameField::new("users", "email", "email_address"))
+                .operation(
+                    AlterField::new("users", "name")
+                        .set_nullable(false)
+                        .set_type(FieldType::VarChar(100)),
+                )
+                .operation(AddIndex::new(
+                    "users",
+                    Index::new("idx_users_email").column("email_address"),
+                ))
+                .operation(RenameTable::new("users", "accounts"))
+                .operation(RunSql::new("ANALYZE")),
+        );
+
+        let state = SchemaState::from_registry(&registry).unwrap();
+        let tables: Vec<&str> = state.tables().iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(tables, vec!["accounts", "posts"]);
+
+        let accounts = state.table("accounts").unwrap();
+        assert_eq!(names(&accounts.fields), vec!["id", "email_address", "name"]);
+        let name = accounts.field("name").unwrap();
+        assert!(!name.nullable);
+        assert_eq!(name.field_type, FieldType::VarChar(100));
+        assert_eq!(accounts.indexes[0].name, "idx_users_email");
+
+        assert_eq!(
+            state.table("posts").unwrap().relations(),
+            vec![Relation {
+                columns: vec!["author_id".to_string()],
+                ref_table: "accounts".to_string(),
+                ref_columns: vec!["id".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn replay_custom_types() {
+        let vector = FieldType::custom(SqlType::new("vector(1536)").on("sqlite", "blob"));
+        let mut state = SchemaState::new();
+        state.apply(
+            &Migration::new("m")
+                .operation(
+                    CreateTable::new("docs")
+                        .add_field(Field::new("id", FieldType::Serial).primary_key())
+                        .add_field(Field::new("title", FieldType::Text)),
+                )
+                .operation(AddField::new(
+                    "docs",
+                    Field::new("embedding", vector.clone()),
+                ))
+                .operation(
+                    AlterField::new("docs", "title")
+                        .set_type(FieldType::custom(SqlType::new("citext"))),
+                ),
+        );
+
+        let docs = state.table("docs").unwrap();
+        assert_eq!(docs.field("embedding").unwrap().field_type, vector);
+        assert_eq!(
+            docs.field("title").unwrap().field_type,
+            FieldType::custom(SqlType::new("citext"))
+        );
+        assert_ne!(docs.field("title").unwrap().field_type, vector);
+    }
+
+    #[test]
+    fn removals_and_constraints() {
+        let mut state = SchemaState::new();
+        let migration = Migration::new("m")
+            .operation(
+                CreateTable::new("users")
+                    .add_field(Field::new("id", FieldType::Serial).primary_key())
+                    .add_field(Field::new("org_id", FieldType::Integer))
+                    .add_field(Field::new("bio", FieldType::Text)),
+            )
+            .operation(CreateTable::new("legacy").field("id", FieldType::Serial))
+            .operation(AddConstraint::new(
+                "users",
+                Constraint::foreign_key(
+                    "fk_users_org",
+                    vec!["org_id".to_string()],
+                    "orgs",
+                    vec!["id".to_string()],
+                ),
+            ))
+            .operation(AddConstraint::new(
+                "users",
+                Constraint::check("chk_id", "id > 0"),
+            ))
+            .operation(RemoveConstraint::new("users", "chk_id"))
+            .operation(AddIndex::new("users", Index::new("idx_bio").column("bio")))
+            .operation(RemoveIndex::new("users", "idx_bio"))
+            .operation(RemoveField::new("users", "bio"))
+            .operation(RenameField::new("users", "org_id", "organization_id"))
+            .operation(DropTable::new("legacy"));
+        state.apply(&migration);
+
+        assert_eq!(state.tables().len(), 1);
+        let users = state.table("users").unwrap();
+        assert_eq!(names(&users.fields), vec!["id", "organization_id"]);
+        assert!(users.indexes.is_empty());
+        assert_eq!(users.constraints.len(), 1);
+        assert_eq!(
+            users.relations()[0].columns,
+            vec!["organization_id".to_string()]
+        );
+    }
+
+    #[test]
+    fn rename_field_repoints_foreign_keys() {
+        let mut state = SchemaState::new();
+        let migration = Migration::new("m")
+            .operation(
+                CreateTable::new("users")
+                    .add_field(Field::new("id", FieldType::Serial).primary_key())
+                    .add_field(Field::new("email", FieldType::Text)),
+            )
+            .operation(
+                CreateTable::new("posts")
+                    .add_field(
+                        Field::new("author_id", FieldType::Integer).references("users", "id"),
+                    )
+                    .add_field(Field::new("author_email", FieldType::Text)),
+            )
+            .operation(AddConstraint::new(
+                "posts",
+                Constraint::foreign_key(
+                    "fk_posts_author_email",
+                    vec!["author_email".to_string()],
+                    "users",
+                    vec!["email".to_string()],
+                ),
+            ))
+            .operation(RenameField::new("users", "id", "user_id"))
+            .operation(RenameField::new("users", "email", "login"));
+        state.apply(&migration);
+
+        let users = state.table("users").unwrap();
+        assert_eq!(names(&users.fields), vec!["user_id", "login"]);
+        let posts = state.table("posts").unwrap();
+        let author = posts.field("author_id").unwrap();
+        assert_eq!(author.references.as_ref().unwrap().column, "user_id");
+        assert_eq!(posts.relations()[1].ref_columns, vec!["login".to_string()]);
+    }
+
+    #[test]
+    fn field_changes_apply() {
+        let mut field = Field::new("status", FieldType::Text).default("'new'");
+        FieldChanges::new()
+            .set_nullable(false)
+            .set_default(None)
+            .apply(&mut field);
+
+        assert!(!field.nullable);
+        assert_eq!(field.default, None);
+        assert_eq!(field.field_type, FieldType::Text);
+    }
+}
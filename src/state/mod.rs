@@ -15,3 +15,72 @@ pub use self::postgres::PostgresMigrationState;
 
 #[cfg(feature = "mysql")]
 pub use self::mysql::MySqlMigrationState;
+
+#[cfg(any(feature = "sqlite", feature = "postgres", feature = "mysql"))]
+use crate::migrator::StoredBackward;
+
+/// Pack statements into one text column as `<byte length>\n<statement>`
+/// records, so statements may themselves contain newlines or semicolons.
+#[cfg(any(feature = "sqlite", feature = "postgres", feature = "mysql"))]
+pub(crate) fn encode_statements(statements: &[String]) -> String {
+    statements
+        .iter()
+        .map(|sql| format!("{}\n{}", sql.len(), sql))
+        .collect()
+}
+
+#[cfg(any(feature = "sqlite", feature = "postgres", feature = "mysql"))]
+pub(crate) fn decode_statements(encoded: &str) -> Result<Vec<String>, String> {
+    let mut statements = Vec::new();
+    let mut rest = encoded;
+    while !rest.is_empty() {
+        let (len, tail) = rest
+            .split_once('\n')
+            .ok_or_else(|| "malformed stored backward SQL".to_string())?;
+        let len: usize = len
+            .parse()
+            .map_err(|_| "malformed stored backward SQL".to_string())?;
+        let sql = tail
+            .get(..len)
+            .ok_or_else(|| "truncated stored backward SQL".to_string())?;
+        statements.push(sql.to_string());
+        rest = &tail[len..];
+    }
+    Ok(statements)
+}
+
+/// A stored backward SQL column and its atomicity, which is NULL in rows
+/// written before it was recorded.
+#[cfg(any(feature = "sqlite", feature = "postgres", feature = "mysql"))]
+pub(crate) fn decode_backward(
+    encoded: Option<String>,
+    atomic: Option<bool>,
+) -> Result<Option<StoredBackward>, String> {
+    encoded
+        .as_deref()
+        .map(|encoded| {
+            Ok(StoredBackward {
+                sql: decode_statements(encoded)?,
+                atomic: atomic.unwrap_or(true),
+            })
+        })
+        .transpose()
+}
+
+#[cfg(all(test, any(feature = "sqlite", feature = "postgres", feature = "mysql")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statements_round_trip() {
+        let statements = vec![
+            "DROP TABLE \"a\"".to_string(),
+            "UPDATE t SET note = 'x;\ny'".to_string(),
+            String::new(),
+        ];
+        let encoded = encode_statements(&statements);
+        assert_eq!(decode_statements(&encoded).unwrap(), statements);
+        assert_eq!(decode_statements("").unwrap(), Vec::<String>::new());
+        assert!(decode_statements("99\nshort").is_err());
+    }
+}
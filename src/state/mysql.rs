@@ -1,7 +1,8 @@
 use mysql::prelude::*;
 use mysql::PooledConn;
 
-use crate::migrator::MigrationStateStore;
+use super::{decode_backward, encode_statements};
+use crate::migrator::{MigrationStateStore, StoredBackward};
 
 const DEFAULT_TABLE_NAME: &str = "schema_migrations";
 
@@ -29,13 +30,53 @@ impl<'a> MySqlMigrationState<'a> {
             .query_drop(format!(
                 "CREATE TABLE IF NOT EXISTS {} (
                     migration_name VARCHAR(255) PRIMARY KEY,
-                    applied BOOLEAN NOT NULL DEFAULT TRUE
+                    applied BOOLEAN NOT NULL DEFAULT TRUE,
+                    backward_sql LONGTEXT NULL,
+                    backward_atomic BOOLEAN NULL,
+                    applied_order BIGINT NULL
                 )",
                 self.table_name
             ))
             .map_err(|e| e.to_string())?;
+
+        // Tables from older versions lack the later columns.
+        self.add_missing_column("backward_sql", "LONGTEXT NULL")?;
+        self.add_missing_column("backward_atomic", "BOOLEAN NULL")?;
+        self.add_missing_column("applied_order", "BIGINT NULL")
+    }
+
+    fn add_missing_column(&mut self, column: &str, definition: &str) -> Result<(), String> {
+        let count: Option<u64> = self
+            .conn
+            .exec_first(
+                "SELECT COUNT(*) FROM information_schema.COLUMNS
+                 WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ? AND COLUMN_NAME = ?",
+                (&self.table_name, column),
+            )
+            .map_err(|e| e.to_string())?;
+        if count == Some(0) {
+            self.conn
+                .query_drop(format!(
+                    "ALTER TABLE {} ADD COLUMN {} {}",
+                    self.table_name, column, definition
+                ))
+                .map_err(|e| e.to_string())?;
+        }
         Ok(())
     }
+
+    /// The next `applied_order`. MySQL can't read the table it inserts into
+    /// from a subquery, so this is a separate query.
+    fn next_applied_order(&mut self) -> Result<u64, String> {
+        let next: Option<u64> = self
+            .conn
+            .query_first(format!(
+                "SELECT COALESCE(MAX(applied_order), 0) + 1 FROM {}",
+                self.table_name
+            ))
+            .map_err(|e| e.to_string())?;
+        Ok(next.unwrap_or(1))
+    }
 }
 
 impl MigrationStateStore for MySqlMigrationState<'_> {
@@ -43,7 +84,8 @@ impl MigrationStateStore for MySqlMigrationState<'_> {
         let names: Vec<String> = self
             .conn
             .query(format!(
-                "SELECT migration_name FROM {} WHERE applied = TRUE ORDER BY migration_name",
+                "SELECT migration_name FROM {} WHERE applied = TRUE
+                 ORDER BY applied_order, migration_name",
                 self.table_name
             ))
             .map_err(|e| e.to_string())?;
@@ -51,15 +93,20 @@ impl MigrationStateStore for MySqlMigrationState<'_> {
         Ok(names)
     }
 
+    // MySQL runs the `ON DUPLICATE KEY UPDATE` assignments left to right,
+    // so `applied_order` is set while `applied` still holds the old value.
     fn mark_applied(&mut self, name: &str) -> Result<(), String> {
+        let order = self.next_applied_order()?;
         self.conn
             .exec_drop(
                 format!(
-                    "INSERT INTO {} (migration_name, applied) VALUES (?, TRUE)
-                     ON DUPLICATE KEY UPDATE applied = TRUE",
+                    "INSERT INTO {} (migration_name, applied, applied_order) VALUES (?, TRUE, ?)
+                     ON DUPLICATE KEY UPDATE
+                     applied_order = IF(applied, applied_order, VALUES(applied_order)),
+                     applied = TRUE",
                     self.table_name
                 ),
-                (name,),
+                (name, order),
             )
             .map_err(|e| e.to_string())?;
         Ok(())
@@ -77,6 +124,51 @@ impl MigrationStateStore for MySqlMigrationState<'_> {
             .map_err(|e| e.to_string())?;
         Ok(())
     }
+
+    fn mark_applied_with_backward(
+        &mut self,
+        name: &str,
+        backward: Option<&StoredBackward>,
+    ) -> Result<(), String> {
+        let order = self.next_applied_order()?;
+        self.conn
+            .exec_drop(
+                format!(
+                    "INSERT INTO {} (migration_name, applied, backward_sql, backward_atomic,
+                                     applied_order)
+                     VALUES (?, TRUE, ?, ?, ?)
+                     ON DUPLICATE KEY UPDATE
+                     applied_order = IF(applied, applied_order, VALUES(applied_order)),
+                     applied = TRUE, backward_sql = VALUES(backward_sql),
+                     backward_atomic = VALUES(backward_atomic)",
+                    self.table_name
+                ),
+                (
+                    name,
+                    backward.map(|b| encode_statements(&b.sql)),
+                    backward.map(|b| b.atomic),
+                    order,
+                ),
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn stored_backward_sql(&mut self, name: &str) -> Result<Option<StoredBackward>, String> {
+        let row: Option<(Option<String>, Option<bool>)> = self
+            .conn
+            .exec_first(
+                format!(
+                    "SELECT backward_sql, backward_atomic FROM {} WHERE migration_name = ?",
+                    self.table_name
+                ),
+                (name,),
+            )
+            .map_err(|e| e.to_string())?;
+
+        let (encoded, atomic) = row.unwrap_or_default();
+        decode_backward(encoded, atomic)
+    }
 }
 
 #[cfg(test)]
@@ -168,6 +260,37 @@ mod tests {
         cleanup_table(&mut conn, table_name);
     }
 
+    #[test]
+    #[ignore = "requires mysql connection"]
+    fn lists_in_apply_order() {
+        let Some(mut conn) = get_test_conn() else {
+            return;
+        };
+        let table_name = "test_apply_order_migrations";
+        cleanup_table(&mut conn, table_name);
+
+        let mut state = MySqlMigrationState::with_table_name(&mut conn, table_name).unwrap();
+
+        state.mark_applied("users.0001_initial").unwrap();
+        state.mark_applied("billing.0001_initial").unwrap();
+        state.mark_applied("audit.0001_initial").unwrap();
+        state.mark_applied("users.0001_initial").unwrap();
+        state.mark_unapplied("billing.0001_initial").unwrap();
+        state.mark_applied("billing.0001_initial").unwrap();
+
+        let applied = state.applied_migrations().unwrap();
+        assert_eq!(
+            applied,
+            vec![
+                "users.0001_initial",
+                "audit.0001_initial",
+                "billing.0001_initial"
+            ]
+        );
+
+        cleanup_table(&mut conn, table_name);
+    }
+
     #[test]
     #[ignore = "requires mysql connection"]
     fn mark_applied_is_idempotent() {
@@ -208,4 +331,34 @@ mod tests {
 
         cleanup_table(&mut conn, table_name);
     }
+
+    #[test]
+    #[ignore = "requires mysql connection"]
+    fn stores_backward_sql() {
+        let Some(mut conn) = get_test_conn() else {
+            return;
+        };
+        let table_name = "test_backward_sql_migrations";
+        cleanup_table(&mut conn, table_name);
+
+        let mut state = MySqlMigrationState::with_table_name(&mut conn, table_name).unwrap();
+        let backward = StoredBackward {
+            sql: vec!["DROP TABLE a".to_string(), "DROP TABLE b".to_string()],
+            atomic: false,
+        };
+
+        state
+            .mark_applied_with_backward("0001_initial", Some(&backward))
+            .unwrap();
+        state.mark_applied_with_backward("0002_data", None).unwrap();
+
+        assert_eq!(
+            state.stored_backward_sql("0001_initial").unwrap(),
+            Some(backward)
+        );
+        assert_eq!(state.stored_backward_sql("0002_data").unwrap(), None);
+        assert_eq!(state.stored_backward_sql("missing").unwrap(), None);
+
+        cleanup_table(&mut conn, table_name);
+    }
 }
@@ -1,6 +1,7 @@
 use postgres::Client;
 
-use crate::migrator::MigrationStateStore;
+use super::{decode_backward, encode_statements};
+use crate::migrator::{MigrationStateStore, StoredBackward};
 
 const DEFAULT_TABLE_NAME: &str = "schema_migrations";
 
@@ -29,15 +30,39 @@ impl<'a> PostgresMigrationState<'a> {
                 &format!(
                     "CREATE TABLE IF NOT EXISTS {} (
                         migration_name TEXT PRIMARY KEY,
-                        applied BOOLEAN NOT NULL DEFAULT TRUE
+                        applied BOOLEAN NOT NULL DEFAULT TRUE,
+                        backward_sql TEXT,
+                        backward_atomic BOOLEAN,
+                        applied_order BIGINT
                     )",
                     self.table_name
                 ),
                 &[],
             )
             .map_err(|e| e.to_string())?;
+
+        // Tables from older versions lack the later columns.
+        self.client
+            .execute(
+                &format!(
+                    "ALTER TABLE {} ADD COLUMN IF NOT EXISTS backward_sql TEXT,
+                     ADD COLUMN IF NOT EXISTS backward_atomic BOOLEAN,
+                     ADD COLUMN IF NOT EXISTS applied_order BIGINT",
+                    self.table_name
+                ),
+                &[],
+            )
+            .map_err(|e| e.to_string())?;
         Ok(())
     }
+
+    /// The next `applied_order`, as a subquery.
+    fn applied_order_sql(&self) -> String {
+        format!(
+            "(SELECT COALESCE(MAX(applied_order), 0) + 1 FROM {})",
+            self.table_name
+        )
+    }
 }
 
 impl MigrationStateStore for PostgresMigrationState<'_> {
@@ -46,7 +71,8 @@ impl MigrationStateStore for PostgresMigrationState<'_> {
             .client
             .query(
                 &format!(
-                    "SELECT migration_name FROM {} WHERE applied = TRUE ORDER BY migration_name",
+                    "SELECT migration_name FROM {} WHERE applied = TRUE
+                     ORDER BY applied_order NULLS FIRST, migration_name",
                     self.table_name
                 ),
                 &[],
@@ -61,9 +87,13 @@ impl MigrationStateStore for PostgresMigrationState<'_> {
         self.client
             .execute(
                 &format!(
-                    "INSERT INTO {} (migration_name, applied) VALUES ($1, TRUE)
-                     ON CONFLICT (migration_name) DO UPDATE SET applied = TRUE",
-                    self.table_name
+                    "INSERT INTO {} AS m (migration_name, applied, applied_order)
+                     VALUES ($1, TRUE, {})
+                     ON CONFLICT (migration_name) DO UPDATE SET applied = TRUE,
+                     applied_order = CASE WHEN m.applied THEN m.applied_order
+                                          ELSE EXCLUDED.applied_order END",
+                    self.table_name,
+                    self.applied_order_sql()
                 ),
                 &[&name],
             )
@@ -83,6 +113,51 @@ impl MigrationStateStore for PostgresMigrationState<'_> {
             .map_err(|e| e.to_string())?;
         Ok(())
     }
+
+    fn mark_applied_with_backward(
+        &mut self,
+        name: &str,
+        backward: Option<&StoredBackward>,
+    ) -> Result<(), String> {
+        let encoded = backward.map(|b| encode_statements(&b.sql));
+        let atomic = backward.map(|b| b.atomic);
+        self.client
+            .execute(
+                &format!(
+                    "INSERT INTO {} AS m (migration_name, applied, backward_sql, backward_atomic,
+                                          applied_order)
+                     VALUES ($1, TRUE, $2, $3, {})
+                     ON CONFLICT (migration_name) DO UPDATE
+                     SET applied = TRUE, backward_sql = EXCLUDED.backward_sql,
+                     backward_atomic = EXCLUDED.backward_atomic,
+                     applied_order = CASE WHEN m.applied THEN m.applied_order
+                                          ELSE EXCLUDED.applied_order END",
+                    self.table_name,
+                    self.applied_order_sql()
+                ),
+                &[&name, &encoded, &atomic],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn stored_backward_sql(&mut self, name: &str) -> Result<Option<StoredBackward>, String> {
+        let row = self
+            .client
+            .query_opt(
+                &format!(
+                    "SELECT backward_sql, backward_atomic FROM {} WHERE migration_name = $1",
+                    self.table_name
+                ),
+                &[&name],
+            )
+            .map_err(|e| e.to_string())?;
+
+        match row {
+            Some(row) => decode_backward(row.get(0), row.get(1)),
+            None => Ok(None),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -174,6 +249,37 @@ mod tests {
         cleanup_table(&mut client, table_name);
     }
 
+    #[test]
+    #[ignore = "requires postgres connection"]
+    fn lists_in_apply_order() {
+        let Some(mut client) = get_test_client() else {
+            return;
+        };
+        let table_name = "test_apply_order_migrations";
+        cleanup_table(&mut client, table_name);
+
+        let mut state = PostgresMigrationState::with_table_name(&mut client, table_name).unwrap();
+
+        state.mark_applied("users.0001_initial").unwrap();
+        state.mark_applied("billing.0001_initial").unwrap();
+        state.mark_applied("audit.0001_initial").unwrap();
+        state.mark_unapplied("billing.0001_initial").unwrap();
+        state
+            .mark_applied_with_backward("billing.0001_initial", None)
+            .unwrap();
+
+        assert_eq!(
+            state.applied_migrations().unwrap(),
+            vec![
+                "users.0001_initial",
+                "audit.0001_initial",
+                "billing.0001_initial"
+            ]
+        );
+
+        cleanup_table(&mut client, table_name);
+    }
+
     #[test]
     #[ignore = "requires postgres connection"]
     fn mark_applied_is_idempotent() {
@@ -214,4 +320,34 @@ mod tests {
 
         cleanup_table(&mut client, table_name);
     }
+
+    #[test]
+    #[ignore = "requires postgres connection"]
+    fn stores_backward_sql() {
+        let Some(mut client) = get_test_client() else {
+            return;
+        };
+        let table_name = "test_backward_sql_migrations";
+        cleanup_table(&mut client, table_name);
+
+        let mut state = PostgresMigrationState::with_table_name(&mut client, table_name).unwrap();
+        let backward = StoredBackward {
+            sql: vec!["DROP TABLE a".to_string(), "DROP TABLE b".to_string()],
+            atomic: false,
+        };
+
+        state
+            .mark_applied_with_backward("0001_initial", Some(&backward))
+            .unwrap();
+        state.mark_applied_with_backward("0002_data", None).unwrap();
+
+        assert_eq!(
+            state.stored_backward_sql("0001_initial").unwrap(),
+            Some(backward)
+        );
+        assert_eq!(state.stored_backward_sql("0002_data").unwrap(), None);
+        assert_eq!(state.stored_backward_sql("missing").unwrap(), None);
+
+        cleanup_table(&mut client, table_name);
+    }
 }
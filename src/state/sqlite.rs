@@ -1,6 +1,7 @@
-use rusqlite::Connection;
+use rusqlite::{Connection, OptionalExtension};
 
-use crate::migrator::MigrationStateStore;
+use super::{decode_backward, encode_statements};
+use crate::migrator::{MigrationStateStore, StoredBackward};
 
 const DEFAULT_TABLE_NAME: &str = "schema_migrations";
 
@@ -29,15 +30,56 @@ impl<'a> SqliteMigrationState<'a> {
                 &format!(
                     "CREATE TABLE IF NOT EXISTS {} (
                         migration_name TEXT PRIMARY KEY,
-                        applied INTEGER NOT NULL DEFAULT 1
+                        applied INTEGER NOT NULL DEFAULT 1,
+                        backward_sql TEXT,
+                        backward_atomic INTEGER,
+                        applied_order INTEGER
                     )",
                     self.table_name
                 ),
                 [],
             )
             .map_err(|e| e.to_string())?;
+
+        // Tables from older versions lack the later columns.
+        self.add_missing_column("backward_sql", "TEXT")?;
+        self.add_missing_column("backward_atomic", "INTEGER")?;
+        self.add_missing_column("applied_order", "INTEGER")
+    }
+
+    fn add_missing_column(&self, column: &str, definition: &str) -> Result<(), String> {
+        let exists: bool = self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = ?1",
+                    self.table_name
+                ),
+                [column],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        if !exists {
+            self.conn
+                .execute(
+                    &format!(
+                        "ALTER TABLE {} ADD COLUMN {} {}",
+                        self.table_name, column, definition
+                    ),
+                    [],
+                )
+                .map_err(|e| e.to_string())?;
+        }
         Ok(())
     }
+
+    /// The next `applied_order`, as a subquery.
+    fn applied_order_sql(&self) -> String {
+        format!(
+            "(SELECT COALESCE(MAX(applied_order), 0) + 1 FROM {})",
+            self.table_name
+        )
+    }
 }
 
 impl MigrationStateStore for SqliteMigrationState<'_> {
@@ -45,7 +87,7 @@ impl MigrationStateStore for SqliteMigrationState<'_> {
         let mut stmt = self
             .conn
             .prepare(&format!(
-                "SELECT migration_name FROM {} WHERE applied = 1 ORDER BY rowid",
+                "SELECT migration_name FROM {} WHERE applied = 1 ORDER BY applied_order, rowid",
                 self.table_name
             ))
             .map_err(|e| e.to_string())?;
@@ -63,9 +105,12 @@ impl MigrationStateStore for SqliteMigrationState<'_> {
         self.conn
             .execute(
                 &format!(
-                    "INSERT INTO {} (migration_name, applied) VALUES (?1, 1)
-                     ON CONFLICT(migration_name) DO UPDATE SET applied = 1",
-                    self.table_name
+                    "INSERT INTO {} (migration_name, applied, applied_order) VALUES (?1, 1, {})
+                     ON CONFLICT(migration_name) DO UPDATE SET applied = 1,
+                     applied_order = CASE WHEN applied = 1 THEN applied_order
+                                          ELSE excluded.applied_order END",
+                    self.table_name,
+                    self.applied_order_sql()
                 ),
                 [name],
             )
@@ -85,6 +130,53 @@ impl MigrationStateStore for SqliteMigrationState<'_> {
             .map_err(|e| e.to_string())?;
         Ok(())
     }
+
+    fn mark_applied_with_backward(
+        &mut self,
+        name: &str,
+        backward: Option<&StoredBackward>,
+    ) -> Result<(), String> {
+        self.conn
+            .execute(
+                &format!(
+                    "INSERT INTO {} (migration_name, applied, backward_sql, backward_atomic,
+                                     applied_order)
+                     VALUES (?1, 1, ?2, ?3, {})
+                     ON CONFLICT(migration_name) DO UPDATE
+                     SET applied = 1, backward_sql = excluded.backward_sql,
+                     backward_atomic = excluded.backward_atomic,
+                     applied_order = CASE WHEN applied = 1 THEN applied_order
+                                          ELSE excluded.applied_order END",
+                    self.table_name,
+                    self.applied_order_sql()
+                ),
+                rusqlite::params![
+                    name,
+                    backward.map(|b| encode_statements(&b.sql)),
+                    backward.map(|b| b.atomic)
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn stored_backward_sql(&mut self, name: &str) -> Result<Option<StoredBackward>, String> {
+        let row: Option<(Option<String>, Option<bool>)> = self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT backward_sql, backward_atomic FROM {} WHERE migration_name = ?1",
+                    self.table_name
+                ),
+                [name],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+
+        let (encoded, atomic) = row.unwrap_or_default();
+        decode_backward(encoded, atomic)
+    }
 }
 
 #[cfg(test)]
@@ -173,6 +265,30 @@ mod tests {
         assert_eq!(applied, vec!["0001_initial"]);
     }
 
+    #[test]
+    fn lists_in_apply_order() {
+        let conn = Connection::open_in_memory().unwrap();
+        let mut state = SqliteMigrationState::new(&conn).unwrap();
+
+        state.mark_applied("users.0001_initial").unwrap();
+        state.mark_applied("billing.0001_initial").unwrap();
+        state.mark_applied("audit.0001_initial").unwrap();
+        state.mark_applied("users.0001_initial").unwrap();
+        state.mark_unapplied("billing.0001_initial").unwrap();
+        state
+            .mark_applied_with_backward("billing.0001_initial", None)
+            .unwrap();
+
+        assert_eq!(
+            state.applied_migrations().unwrap(),
+            vec![
+                "users.0001_initial",
+                "audit.0001_initial",
+                "billing.0001_initial"
+            ]
+        );
+    }
+
     #[test]
     fn unapplied_migration_not_in_list() {
         let conn = Connection::open_in_memory().unwrap();
@@ -185,4 +301,58 @@ mod tests {
         let applied = state.applied_migrations().unwrap();
         assert_eq!(applied, vec!["0002_b"]);
     }
+
+    #[test]
+    fn stores_backward_sql() {
+        let conn = Connection::open_in_memory().unwrap();
+        let mut state = SqliteMigrationState::new(&conn).unwrap();
+        let backward = StoredBackward {
+            sql: vec!["DROP TABLE a".to_string(), "DROP TABLE b".to_string()],
+            atomic: false,
+        };
+
+        state
+            .mark_applied_with_backward("0001_initial", Some(&backward))
+            .unwrap();
+        state.mark_applied_with_backward("0002_data", None).unwrap();
+        state.mark_applied("0003_plain").unwrap();
+
+        assert_eq!(
+            state.applied_migrations().unwrap(),
+            vec!["0001_initial", "0002_data", "0003_plain"]
+        );
+        assert_eq!(
+            state.stored_backward_sql("0001_initial").unwrap(),
+            Some(backward)
+        );
+        assert_eq!(state.stored_backward_sql("0002_data").unwrap(), None);
+        assert_eq!(state.stored_backward_sql("0003_plain").unwrap(), None);
+        assert_eq!(state.stored_backward_sql("missing").unwrap(), None);
+    }
+
+    #[test]
+    fn upgrades_table_without_backward_sql() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE schema_migrations (
+                migration_name TEXT PRIMARY KEY,
+                applied INTEGER NOT NULL DEFAULT 1
+            );
+            INSERT INTO schema_migrations (migration_name) VALUES ('0001_initial');",
+        )
+        .unwrap();
+
+        let mut state = SqliteMigrationState::new(&conn).unwrap();
+        assert_eq!(state.applied_migrations().unwrap(), vec!["0001_initial"]);
+        assert_eq!(state.stored_backward_sql("0001_initial").unwrap(), None);
+
+        let backward = StoredBackward {
+            sql: vec!["DROP TABLE a".to_string()],
+            atomic: true,
+        };
+        state
+            .mark_applied_with_backward("0002_a", Some(&backward))
+            .unwrap();
+        assert_eq!(state.stored_backward_sql("0002_a").unwrap(), Some(backward));
+    }
 }
@@ -0,0 +1 @@
+CREATE TABLE settings (key TEXT PRIMARY KEY, value TEXT)
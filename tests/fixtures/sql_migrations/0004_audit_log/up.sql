@@ -0,0 +1,11 @@
+-- Audit log only needs the users table.
+-- depends_on: 0001_create_users
+
+CREATE TABLE audit_log (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users (id), note TEXT);
+
+CREATE INDEX idx_audit_log_user ON audit_log (user_id);
+
+CREATE TRIGGER users_audit AFTER INSERT ON users
+BEGIN
+    INSERT INTO audit_log (user_id, note) VALUES (NEW.id, 'created; welcome');
+END;
@@ -483,3 +483,54 @@ fn transaction_support() {
 
     cleanup_tables(&mut client_ref.borrow_mut());
 }
+
+#[test]
+#[ignore = "requires postgres connection"]
+fn enum_type_lifecycle() {
+    let Some(mut client) = get_test_client() else {
+        eprintln!("Skipping test: no postgres connection");
+        return;
+    };
+    let _ = client.execute("DROP TABLE IF EXISTS orders", &[]);
+    let _ = client.execute("DROP TYPE IF EXISTS order_status", &[]);
+
+    let status = FieldType::enumeration("order_status", &["pending", "shipped"]);
+    let mut registry = MigrationRegistry::new();
+    registry.register(
+        Migration::new("0001_orders")
+            .operation(CreateEnum::new("order_status", &["pending", "shipped"]))
+            .operation(
+                CreateTable::new("orders").add_field(Field::new("status", status).not_null()),
+            ),
+    );
+    registry.register(
+        Migration::new("0002_paid")
+            .depends_on(&["0001_orders"])
+            .operation(AddEnumValue::new("order_status", "paid").after("pending")),
+    );
+    registry.register(
+        Migration::new("0003_sent")
+            .depends_on(&["0002_paid"])
+            .operation(RenameEnumValue::new("order_status", "shipped", "sent")),
+    );
+
+    let mut migrator = Migrator::new(&registry, &Postgres, InMemoryState::new());
+    migrator
+        .migrate_forward(|sql| {
+            client
+                .execute(sql, &[])
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+        .unwrap();
+
+    client
+        .execute("INSERT INTO orders VALUES ('paid'), ('sent')", &[])
+        .unwrap();
+    assert!(client
+        .execute("INSERT INTO orders VALUES ('shipped')", &[])
+        .is_err());
+
+    let _ = client.execute("DROP TABLE IF EXISTS orders", &[]);
+    let _ = client.execute("DROP TYPE IF EXISTS order_status", &[]);
+}
@@ -0,0 +1,134 @@
+#![cfg(feature = "macros")]
+
+use cetane::prelude::*;
+use rusqlite::Connection;
+
+fn registry() -> MigrationRegistry {
+    let mut registry = MigrationRegistry::new();
+    registry.register_all(cetane::embed_sql_migrations!(
+        "tests/fixtures/sql_migrations"
+    ));
+    registry
+}
+
+#[test]
+fn embeds_one_migration_per_directory() {
+    let registry = registry();
+
+    let names: Vec<_> = registry.all().map(|m| m.name.as_ref()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "0001_create_users",
+            "0002_index_users",
+            "0003_portable",
+            "0004_audit_log"
+        ]
+    );
+}
+
+#[test]
+fn infers_sequential_dependencies() {
+    let registry = registry();
+
+    assert!(registry
+        .get("0001_create_users")
+        .unwrap()
+        .dependencies
+        .is_empty());
+    assert_eq!(
+        registry.get("0002_index_users").unwrap().dependencies,
+        vec!["0001_create_users"]
+    );
+    assert_eq!(
+        registry.get("0003_portable").unwrap().dependencies,
+        vec!["0002_index_users"]
+    );
+}
+
+#[test]
+fn dependency_header_overrides_inferred_dependency() {
+    let registry = registry();
+
+    assert_eq!(
+        registry.get("0004_audit_log").unwrap().dependencies,
+        vec!["0001_create_users"]
+    );
+}
+
+#[test]
+fn down_sql_makes_migration_reversible() {
+    let registry = registry();
+
+    let create_users = registry.get("0001_create_users").unwrap();
+    assert!(create_users.is_reversible());
+    assert_eq!(
+        create_users.backward_sql(&Sqlite).unwrap(),
+        vec!["DROP TABLE users"]
+    );
+
+    assert!(!registry.get("0002_index_users").unwrap().is_reversible());
+}
+
+#[test]
+fn backend_specific_files_override_generic_sql() {
+    let registry = registry();
+    let portable = registry.get("0003_portable").unwrap();
+
+    assert!(portable.forward_sql(&Postgres)[0].contains("JSONB"));
+    assert!(portable.forward_sql(&Sqlite)[0].contains("value TEXT"));
+    assert!(portable.forward_sql(&MySql)[0].contains("value TEXT"));
+    assert_eq!(
+        portable.backward_sql(&Postgres).unwrap(),
+        vec!["DROP TABLE settings"]
+    );
+}
+
+#[test]
+fn embedded_migrations_run_on_sqlite() {
+    let registry = registry();
+    let conn = Connection::open_in_memory().unwrap();
+    let mut migrator = Migrator::new(&registry, &Sqlite, InMemoryState::new());
+
+    let applied = migrator
+        .migrate_forward(|sql| conn.execute(sql, []).map(|_| ()).map_err(|e| e.to_string()))
+        .unwrap();
+    assert_eq!(applied.len(), 4);
+
+    let tables: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name IN ('users', 'settings', 'audit_log')",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(tables, 3);
+
+    conn.execute("INSERT INTO users (email) VALUES ('a@example.com')", [])
+        .unwrap();
+    let note: String = conn
+        .query_row("SELECT note FROM audit_log", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(note, "created; welcome");
+}
+
+#[test]
+fn multi_statement_files_split_into_statements() {
+    let registry = registry();
+    let audit_log = registry.get("0004_audit_log").unwrap();
+
+    let forward = audit_log.forward_sql(&Sqlite);
+    assert_eq!(forward.len(), 3);
+    assert!(forward[1].starts_with("CREATE INDEX"));
+    assert!(forward[2].ends_with("END"));
+    assert_eq!(
+        audit_log.backward_sql(&Sqlite).unwrap(),
+        vec!["DROP TRIGGER users_audit", "DROP TABLE audit_log"]
+    );
+}
+
+#[test]
+fn accepts_raw_string_paths() {
+    let migrations = cetane::embed_sql_migrations!(r"tests/fixtures/sql_migrations");
+    assert_eq!(migrations.len(), registry().len());
+}
@@ -431,3 +431,350 @@ fn transaction_rollback_on_failure() {
         "Failed migration's insert should have been rolled back"
     );
 }
+
+#[test]
+fn import_refinery_history() {
+    use cetane::import::{import, ExternalMigration, ExternalTool, MatchRule};
+
+    let conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(
+        "CREATE TABLE refinery_schema_history (
+            version INTEGER PRIMARY KEY,
+            name TEXT,
+            applied_on TEXT,
+            checksum TEXT
+        );
+        INSERT INTO refinery_schema_history (version, name) VALUES (1, 'create_users');
+        INSERT INTO refinery_schema_history (version, name) VALUES (2, 'add_user_name');",
+    )
+    .unwrap();
+
+    let registry = setup_registry();
+    let mut state = InMemoryState::new();
+
+    let imported = import(
+        ExternalTool::Refinery,
+        &Sqlite,
+        &registry,
+        &MatchRule::VersionPrefix,
+        |sql| {
+            let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map([], |row| {
+                    Ok(ExternalMigration {
+                        version: row.get(0)?,
+                        name: row.get(1)?,
+                    })
+                })
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())
+        },
+        &mut state,
+    )
+    .unwrap();
+
+    assert_eq!(imported, vec!["0001_create_users", "0002_add_user_name"]);
+
+    let mut migrator = Migrator::new(&registry, &Sqlite, state);
+    let pending = migrator.plan_forward().unwrap();
+    assert!(!pending.contains(&"0001_create_users"));
+    assert!(!pending.contains(&"0002_add_user_name"));
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn rollback_ghost_migration_from_stored_sql() {
+    let conn = Connection::open_in_memory().unwrap();
+    let execute = |sql: &str| conn.execute_batch(sql).map_err(|e| e.to_string());
+
+    let registry = setup_registry();
+    let state = SqliteMigrationState::new(&conn).unwrap();
+    let mut migrator = Migrator::new(&registry, &Sqlite, state);
+    migrator.migrate_forward(execute).unwrap();
+
+    // Only the first migration survives a revert of the code base.
+    let mut reverted = MigrationRegistry::new();
+    reverted.register(
+        Migration::new("0001_create_users")
+            .operation(CreateTable::new("users").field("id", FieldType::Serial)),
+    );
+    let mut migrator = Migrator::new(&reverted, &Sqlite, migrator.into_state());
+
+    let ghosts = migrator.rollback_ghosts(execute).unwrap();
+    assert_eq!(
+        ghosts,
+        vec![
+            "0004_add_post_index",
+            "0003_create_posts",
+            "0002_add_user_name"
+        ]
+    );
+
+    let posts: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='posts'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(posts, 0);
+    assert!(conn.prepare("SELECT name FROM users").is_err());
+    assert_eq!(
+        migrator.state_mut().applied_migrations().unwrap(),
+        vec!["0001_create_users"]
+    );
+}
+
+#[test]
+fn insert_rows_seed_and_rollback() {
+    let conn = Connection::open_in_memory().unwrap();
+    let execute = |sql: &str| conn.execute_batch(sql).map_err(|e| e.to_string());
+
+    let mut registry = MigrationRegistry::new();
+    registry.register(
+        Migration::new("0001_roles")
+            .operation(
+                CreateTable::new("roles")
+                    .add_field(Field::new("id", FieldType::Integer).primary_key())
+                    .add_field(Field::new("name", FieldType::Text).not_null()),
+            )
+            .operation(
+                InsertRows::new("roles", &["id", "name"])
+                    .row(vec![1.into(), "admin".into()])
+                    .row(vec![2.into(), "o'brien".into()]),
+            ),
+    );
+    registry.register(
+        Migration::new("0002_more_roles")
+            .depends_on(&["0001_roles"])
+            .operation(
+                InsertRows::new("roles", &["id", "name"]).row(vec![3.into(), "guest".into()]),
+            ),
+    );
+
+    let mut migrator = Migrator::new(&registry, &Sqlite, InMemoryState::new());
+    migrator.migrate_forward(execute).unwrap();
+
+    let names = |conn: &Connection| -> Vec<String> {
+        let mut stmt = conn.prepare("SELECT name FROM roles ORDER BY id").unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    };
+    assert_eq!(names(&conn), vec!["admin", "o'brien", "guest"]);
+
+    migrator
+        .migrate_backward(Some("0002_more_roles"), execute)
+        .unwrap();
+    assert_eq!(names(&conn), vec!["admin", "o'brien"]);
+}
+
+#[test]
+fn run_sql_bind_values_reach_the_executor() {
+    use rusqlite::types::Value as SqliteValue;
+
+    fn to_sqlite(value: &Value) -> SqliteValue {
+        match value {
+            Value::Int(Some(n)) => SqliteValue::Integer(i64::from(*n)),
+            Value::String(Some(s)) => SqliteValue::Text(s.to_string()),
+            _ => SqliteValue::Null,
+        }
+    }
+
+    let conn = Connection::open_in_memory().unwrap();
+    let mut registry = MigrationRegistry::new();
+    registry.register(
+        Migration::new("0001_settings")
+            .operation(RunSql::reversible(
+                "CREATE TABLE settings (key TEXT, value TEXT, version INTEGER)",
+                "DROP TABLE settings",
+            ))
+            .operation(
+                RunSql::reversible(
+                    "INSERT INTO settings VALUES ($1, $2, $3)",
+                    "DELETE FROM settings WHERE key = $1",
+                )
+                .bind("motd")
+                .bind("it's '; DROP TABLE settings; --")
+                .bind(2)
+                .bind_reverse("motd"),
+            ),
+    );
+
+    let mut executed = Vec::new();
+    let mut execute = |sql: &str, values: &[Value]| {
+        executed.push(sql.to_string());
+        let params: Vec<SqliteValue> = values.iter().map(to_sqlite).collect();
+        conn.execute(sql, rusqlite::params_from_iter(params))
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    };
+
+    let mut migrator = Migrator::new(&registry, &Sqlite, InMemoryState::new());
+    migrator.migrate_forward_with_params(&mut execute).unwrap();
+
+    let (value, version): (String, i64) = conn
+        .query_row(
+            "SELECT value, version FROM settings WHERE key = 'motd'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(value, "it's '; DROP TABLE settings; --");
+    assert_eq!(version, 2);
+
+    migrator
+        .migrate_backward_with_params(None, &mut execute)
+        .unwrap();
+
+    assert_eq!(
+        executed[1..],
+        [
+            "INSERT INTO settings VALUES (?, ?, ?)",
+            "DELETE FROM settings WHERE key = ?",
+            "DROP TABLE settings",
+        ]
+    );
+}
+
+#[test]
+fn typed_defaults_apply_on_sqlite() {
+    let conn = Connection::open_in_memory().unwrap();
+    let mut registry = MigrationRegistry::new();
+    registry.register(
+        Migration::new("0001_create_accounts").operation(
+            CreateTable::new("accounts")
+                .add_field(Field::new("id", FieldType::Serial).primary_key())
+                .add_field(
+                    Field::new("status", FieldType::Text).default(DefaultValue::string("it's new")),
+                )
+                .add_field(Field::new("active", FieldType::Boolean).default(true))
+                .add_field(Field::new("score", FieldType::Integer).default(-1))
+                .add_field(
+                    Field::new("token", FieldType::Uuid).default(DefaultValue::GeneratedUuid),
+                )
+                .add_field(
+                    Field::new("created_at", FieldType::Timestamp)
+                        .default(DefaultValue::CurrentTimestamp),
+                ),
+        ),
+    );
+
+    let mut migrator = Migrator::new(&registry, &Sqlite, InMemoryState::new());
+    migrator
+        .migrate_forward(|sql| conn.execute(sql, []).map(|_| ()).map_err(|e| e.to_string()))
+        .unwrap();
+    conn.execute("INSERT INTO accounts DEFAULT VALUES", [])
+        .unwrap();
+    conn.execute("INSERT INTO accounts DEFAULT VALUES", [])
+        .unwrap();
+
+    let (status, active, score, token, created_at): (String, i64, i64, String, String) = conn
+        .query_row(
+            "SELECT status, active, score, token, created_at FROM accounts WHERE id = 1",
+            [],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            },
+        )
+        .unwrap();
+    assert_eq!(status, "it's new");
+    assert_eq!(active, 1);
+    assert_eq!(score, -1);
+    assert_eq!(token.len(), 36);
+    assert_eq!(&token[14..15], "4");
+    assert!(!created_at.is_empty());
+
+    let distinct_tokens: i64 = conn
+        .query_row("SELECT COUNT(DISTINCT token) FROM accounts", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(distinct_tokens, 2);
+}
+
+#[test]
+fn enum_column_checks_variants_on_sqlite() {
+    let conn = Connection::open_in_memory().unwrap();
+    let mut registry = MigrationRegistry::new();
+    registry.register(
+        Migration::new("0001_orders")
+            .operation(CreateEnum::new("order_status", &["pending", "shipped"]))
+            .operation(
+                CreateTable::new("orders").add_field(
+                    Field::new(
+                        "status",
+                        FieldType::enumeration("order_status", &["pending", "shipped"]),
+                    )
+                    .not_null(),
+                ),
+            ),
+    );
+
+    let mut migrator = Migrator::new(&registry, &Sqlite, InMemoryState::new());
+    migrator
+        .migrate_forward(|sql| conn.execute(sql, []).map(|_| ()).map_err(|e| e.to_string()))
+        .unwrap();
+
+    conn.execute("INSERT INTO orders VALUES ('shipped')", [])
+        .unwrap();
+    let error = conn
+        .execute("INSERT INTO orders VALUES ('lost')", [])
+        .unwrap_err();
+    assert!(error.to_string().contains("CHECK constraint failed"));
+}
+
+#[test]
+fn custom_type_renders_for_sqlite() {
+    let conn = Connection::open_in_memory().unwrap();
+    let citext = FieldType::custom(SqlType::new("citext").on("sqlite", "text COLLATE NOCASE"));
+    let mut registry = MigrationRegistry::new();
+    registry.register(
+        Migration::new("0001_tags")
+            .operation(CreateTable::new("tags").add_field(Field::new("name", citext).unique())),
+    );
+
+    let mut migrator = Migrator::new(&registry, &Sqlite, InMemoryState::new());
+    migrator
+        .migrate_forward(|sql| conn.execute(sql, []).map(|_| ()).map_err(|e| e.to_string()))
+        .unwrap();
+
+    conn.execute("INSERT INTO tags VALUES ('Rust')", [])
+        .unwrap();
+    let error = conn
+        .execute("INSERT INTO tags VALUES ('rust')", [])
+        .unwrap_err();
+    assert!(error.to_string().contains("UNIQUE constraint failed"));
+}
+
+#[test]
+fn money_has_numeric_affinity_on_sqlite() {
+    let conn = Connection::open_in_memory().unwrap();
+    let mut registry = MigrationRegistry::new();
+    registry.register(
+        Migration::new("0001_prices").operation(
+            CreateTable::new("prices").add_field(Field::new("amount", FieldType::Money)),
+        ),
+    );
+
+    let mut migrator = Migrator::new(&registry, &Sqlite, InMemoryState::new());
+    migrator
+        .migrate_forward(|sql| conn.execute(sql, []).map(|_| ()).map_err(|e| e.to_string()))
+        .unwrap();
+
+    // NUMERIC affinity stores whole amounts as integers; REAL would not.
+    conn.execute("INSERT INTO prices VALUES ('12.00')", [])
+        .unwrap();
+    let stored: String = conn
+        .query_row("SELECT typeof(amount) FROM prices", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(stored, "integer");
+}